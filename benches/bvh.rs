@@ -0,0 +1,77 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use raytracer::descartes::{Point3D, Vector3D};
+use raytracer::obj::ObjFile;
+use raytracer::raycasting::Ray;
+use raytracer::world::World;
+use std::f64::consts::PI;
+
+/// An OBJ sphere of `rings * segments * 2` triangles, roughly what a
+/// detailed imported mesh looks like.
+fn tessellated_sphere(rings: usize, segments: usize) -> String {
+    let mut obj = String::new();
+    for ring in 0..=rings {
+        let theta = PI * ring as f64 / rings as f64;
+        for segment in 0..segments {
+            let phi = 2.0 * PI * segment as f64 / segments as f64;
+            obj.push_str(&format!(
+                "v {} {} {}\n",
+                theta.sin() * phi.cos(),
+                theta.cos(),
+                theta.sin() * phi.sin()
+            ));
+        }
+    }
+
+    let vertex = |ring: usize, segment: usize| ring * segments + segment % segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            obj.push_str(&format!(
+                "f {} {} {} {}\n",
+                vertex(ring, segment),
+                vertex(ring, segment + 1),
+                vertex(ring + 1, segment + 1),
+                vertex(ring + 1, segment)
+            ));
+        }
+    }
+    obj
+}
+
+fn rays() -> Vec<Ray> {
+    (0..64)
+        .map(|i| {
+            let (x, y) = ((i % 8) as f64 / 4.0 - 1.0, (i / 8) as f64 / 4.0 - 1.0);
+            Ray::new(
+                Point3D::new(0.0, 0.0, -5.0),
+                Vector3D::new(x, y, 5.0).normalize(),
+            )
+        })
+        .collect()
+}
+
+fn mesh_intersection(c: &mut Criterion) {
+    let mut world = World::new();
+    ObjFile::parse(&tessellated_sphere(100, 100))
+        .unwrap()
+        .into_group(&mut world);
+    let rays = rays();
+    // Build the hierarchy up front rather than in the first sample.
+    world.intersect(&rays[0]);
+
+    let mut group = c.benchmark_group("20k triangle mesh, 64 rays");
+    group.sample_size(10);
+    group.bench_function("bvh", |b| {
+        b.iter(|| rays.iter().map(|r| world.intersect(r).len()).sum::<usize>())
+    });
+    group.bench_function("exhaustive", |b| {
+        b.iter(|| {
+            rays.iter()
+                .map(|r| world.intersect_exhaustive(r).len())
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, mesh_intersection);
+criterion_main!(benches);
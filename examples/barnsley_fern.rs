@@ -1,5 +1,5 @@
 use rand::{rngs::ThreadRng, Rng};
-use raytracer::canvas::Canvas;
+use raytracer::canvas::{Canvas, PpmFormat};
 use raytracer::colour::Colour;
 use raytracer::descartes::Point3D;
 
@@ -21,7 +21,9 @@ fn main() {
         pt = next_point(&mut rng, pt);
     }
 
-    canvas.to_ppm("fern.ppm");
+    canvas
+        .save_ppm("fern.ppm", PpmFormat::Binary)
+        .expect("could not write fern.ppm");
 }
 
 fn next_point(rng: &mut ThreadRng, point: Point3D<f64>) -> Point3D<f64> {
@@ -1,4 +1,4 @@
-use raytracer::canvas::Canvas;
+use raytracer::canvas::{Canvas, PpmFormat};
 use raytracer::colour::Colour;
 use raytracer::descartes::{Point3D, Vector3D};
 
@@ -39,7 +39,9 @@ fn main() {
         p = tick(p, &e)
     }
 
-    canvas.to_ppm("cannon.ppm");
+    canvas
+        .save_ppm("cannon.ppm", PpmFormat::Binary)
+        .expect("could not write cannon.ppm");
 
     println!("Took {} ticks to land", timer)
 }
@@ -1,4 +1,4 @@
-use raytracer::canvas::Canvas;
+use raytracer::canvas::{Canvas, PpmFormat};
 use raytracer::colour::Colour;
 use raytracer::descartes::Point3D;
 use raytracer::transformations::Transformation;
@@ -25,7 +25,9 @@ fn main() {
         write_blot(num, &mut canvas, blue)
     }
 
-    canvas.to_ppm("clock.ppm");
+    canvas
+        .save_ppm("clock.ppm", PpmFormat::Binary)
+        .expect("could not write clock.ppm");
 }
 
 fn write_blot(num: Point3D<f64>, canvas: &mut Canvas, colour: Colour) {
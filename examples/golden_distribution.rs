@@ -21,7 +21,7 @@ fn main() {
         .arg("rgba")
         // The size of the video is 3840x2160
         .arg("-s")
-        .arg(&format!("{}x{}", width, height))
+        .arg(format!("{}x{}", width, height))
         // 60 frames per second
         .arg("-r")
         .arg("120")
@@ -1,5 +1,5 @@
 use rand::{distributions::Uniform, Rng};
-use raytracer::canvas::Canvas;
+use raytracer::canvas::{Canvas, PpmFormat};
 use raytracer::colour::Colour;
 use raytracer::descartes::Point3D;
 use raytracer::transformations::Transformation;
@@ -22,7 +22,7 @@ fn main() {
     // Vertices
     for i in 0..3 {
         let rotation = flipper.clone() * Transformation::rotation_z(((2.0 * PI) / 3.0) * i as f64);
-        let point = centraliser.clone() * (rotation * center.clone());
+        let point = centraliser.clone() * (rotation * center);
 
         vertices.push(point)
     }
@@ -50,5 +50,7 @@ fn main() {
         p = Point3D::new((p.x() + vn.x()) / 2.0, (p.y() + vn.y()) / 2.0, 0.0)
     }
 
-    canvas.to_ppm("sierpinski.ppm")
+    canvas
+        .save_ppm("sierpinski.ppm", PpmFormat::Binary)
+        .expect("could not write sierpinski.ppm");
 }
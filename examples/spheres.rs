@@ -0,0 +1,58 @@
+use raytracer::camera::Camera;
+use raytracer::canvas::PpmFormat;
+use raytracer::colour::Colour;
+use raytracer::descartes::{Point3D, Vector3D};
+use raytracer::lighting::PointLight;
+use raytracer::transformations::Transformation;
+use raytracer::world::World;
+use std::f64::consts::PI;
+
+fn main() {
+    let mut world = World::new();
+    world.add_light(PointLight::new(
+        Point3D::new(-10.0, 10.0, -10.0),
+        Colour::white(),
+    ));
+
+    let floor = world.plane();
+    let m = floor.material_mut();
+    m.set_colour(Colour::new(1.0, 0.9, 0.9));
+    m.set_specular(0.0);
+
+    let middle = world.sphere();
+    middle.set_transform(Transformation::translation(-0.5, 1.0, 0.5));
+    let m = middle.material_mut();
+    m.set_colour(Colour::new(0.1, 1.0, 0.5));
+    m.set_diffuse(0.7);
+    m.set_specular(0.3);
+
+    let right = world.sphere();
+    right.set_transform(
+        Transformation::translation(1.5, 0.5, -0.5) * Transformation::scaling(0.5, 0.5, 0.5),
+    );
+    let m = right.material_mut();
+    m.set_colour(Colour::new(0.5, 1.0, 0.1));
+    m.set_diffuse(0.7);
+    m.set_specular(0.3);
+
+    let left = world.sphere();
+    left.set_transform(
+        Transformation::translation(-1.5, 0.33, -0.75) * Transformation::scaling(0.33, 0.33, 0.33),
+    );
+    let m = left.material_mut();
+    m.set_colour(Colour::new(1.0, 0.8, 0.1));
+    m.set_diffuse(0.7);
+    m.set_specular(0.3);
+
+    let mut camera = Camera::new(640, 320, PI / 3.0);
+    camera.set_transform(Transformation::view_transform(
+        Point3D::new(0.0, 1.5, -5.0),
+        Point3D::new(0.0, 1.0, 0.0),
+        Vector3D::new(0.0, 1.0, 0.0),
+    ));
+
+    camera
+        .render(&world)
+        .save_ppm("spheres.ppm", PpmFormat::Binary)
+        .expect("could not write spheres.ppm");
+}
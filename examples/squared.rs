@@ -1,4 +1,7 @@
-use raytracer::{canvas::Canvas, colour::Colour};
+use raytracer::{
+    canvas::{Canvas, PpmFormat},
+    colour::Colour,
+};
 
 fn main() {
     let width = 640;
@@ -14,5 +17,7 @@ fn main() {
         }
     }
 
-    canvas.to_ppm("squared.ppm");
+    canvas
+        .save_ppm("squared.ppm", PpmFormat::Binary)
+        .expect("could not write squared.ppm");
 }
@@ -0,0 +1,234 @@
+use crate::descartes::Point3D;
+use crate::matrix::M4;
+use crate::raycasting::Ray;
+
+/// An axis-aligned box enclosing a shape. Either corner may be infinite
+/// for shapes which extend forever, like planes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bounds {
+    min: Point3D<f64>,
+    max: Point3D<f64>,
+}
+
+impl Bounds {
+    pub fn new(min: Point3D<f64>, max: Point3D<f64>) -> Self {
+        Bounds { min, max }
+    }
+
+    /// A box containing nothing at all, which grows to fit whatever it
+    /// is combined with.
+    pub fn empty() -> Self {
+        Bounds::from_points(&[])
+    }
+
+    /// The smallest box containing every one of `points`.
+    pub fn from_points(points: &[Point3D<f64>]) -> Self {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for p in points {
+            for (axis, v) in [p.x(), p.y(), p.z()].iter().enumerate() {
+                min[axis] = min[axis].min(*v);
+                max[axis] = max[axis].max(*v);
+            }
+        }
+
+        Bounds::new(
+            Point3D::new(min[0], min[1], min[2]),
+            Point3D::new(max[0], max[1], max[2]),
+        )
+    }
+
+    pub fn min(&self) -> Point3D<f64> {
+        self.min
+    }
+
+    pub fn max(&self) -> Point3D<f64> {
+        self.max
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x() > self.max.x() || self.min.y() > self.max.y() || self.min.z() > self.max.z()
+    }
+
+    /// Whether the box has a finite size, which it won't for empty
+    /// boxes or those around infinite shapes.
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|p| p.x().is_finite() && p.y().is_finite() && p.z().is_finite())
+    }
+
+    /// The smallest box containing both this one and `other`.
+    pub fn union(&self, other: &Bounds) -> Self {
+        // An empty box's corners are inverted, so mustn't be treated
+        // as real points.
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        Bounds::from_points(&[self.min, self.max, other.min, other.max])
+    }
+
+    pub fn centroid(&self) -> Point3D<f64> {
+        Point3D::new(
+            (self.min.x() + self.max.x()) / 2.0,
+            (self.min.y() + self.max.y()) / 2.0,
+            (self.min.z() + self.max.z()) / 2.0,
+        )
+    }
+
+    /// The box around this one once it has been moved by `matrix`. All
+    /// eight corners are transformed, so a rotated box may grow.
+    pub fn transform(&self, matrix: M4) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+
+        let mut corners = Vec::with_capacity(8);
+        for x in [self.min.x(), self.max.x()].iter() {
+            for y in [self.min.y(), self.max.y()].iter() {
+                for z in [self.min.z(), self.max.z()].iter() {
+                    corners.push(transform_point(matrix, [*x, *y, *z]));
+                }
+            }
+        }
+        Bounds::from_points(&corners)
+    }
+
+    /// Whether the line along `ray` passes through the box, using the
+    /// same slab test as `Cube`. Hits behind the ray's origin count, as
+    /// callers still want those intersections.
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        let (origin, direction) = (ray.origin(), ray.direction());
+        let mut tmin = f64::NEG_INFINITY;
+        let mut tmax = f64::INFINITY;
+
+        for (o, d, min, max) in [
+            (origin.x(), direction.x(), self.min.x(), self.max.x()),
+            (origin.y(), direction.y(), self.min.y(), self.max.y()),
+            (origin.z(), direction.z(), self.min.z(), self.max.z()),
+        ]
+        .iter()
+        {
+            let inverse = 1.0 / d;
+            let mut t0 = (min - o) * inverse;
+            let mut t1 = (max - o) * inverse;
+            if inverse < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NaNs from an origin exactly on an edge of the slab are
+            // skipped, since `max` and `min` prefer the other value.
+            tmin = tmin.max(t0);
+            tmax = tmax.min(t1);
+        }
+
+        tmin <= tmax
+    }
+}
+
+/// Multiplies out a point, skipping terms with a zero coefficient so
+/// that infinite coordinates don't turn into NaN.
+fn transform_point(matrix: M4, point: [f64; 3]) -> Point3D<f64> {
+    let point = [point[0], point[1], point[2], 1.0];
+    let mut result = [0.0; 3];
+    for (r, value) in result.iter_mut().enumerate() {
+        *value = (0..4)
+            .filter(|c| matrix.idx(r, *c) != 0.0)
+            .map(|c| matrix.idx(r, c) * point[c])
+            .sum();
+    }
+    Point3D::new(result[0], result[1], result[2])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::descartes::Vector3D;
+    use crate::transformations::Transformation;
+    use std::f64::consts::PI;
+
+    fn unit_box() -> Bounds {
+        Bounds::new(Point3D::new(-1.0, -1.0, -1.0), Point3D::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn empty_bounds() {
+        let b = Bounds::empty();
+
+        assert!(b.is_empty());
+        assert!(!b.is_finite());
+        assert!(!unit_box().is_empty());
+    }
+
+    #[test]
+    fn union_of_bounds() {
+        let a = Bounds::new(Point3D::new(-5.0, -2.0, 0.0), Point3D::new(7.0, 4.0, 4.0));
+        let b = Bounds::new(Point3D::new(8.0, -7.0, -2.0), Point3D::new(14.0, 2.0, 8.0));
+        let u = a.union(&b);
+
+        assert_eq!(u.min(), Point3D::new(-5.0, -7.0, -2.0));
+        assert_eq!(u.max(), Point3D::new(14.0, 4.0, 8.0));
+        assert_eq!(a.union(&Bounds::empty()), a);
+        assert_eq!(Bounds::empty().union(&a), a);
+    }
+
+    #[test]
+    fn transform_bounds() {
+        let m =
+            (Transformation::rotation_x(PI / 4.0) * Transformation::rotation_y(PI / 4.0)).matrix();
+        let b = unit_box().transform(m);
+        let (x, yz) = (2.0_f64.sqrt(), 1.70711);
+
+        assert_eq!(b.min(), Point3D::new(-x, -yz, -yz));
+        assert_eq!(b.max(), Point3D::new(x, yz, yz));
+    }
+
+    #[test]
+    fn transform_infinite_bounds() {
+        let plane = Bounds::new(
+            Point3D::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point3D::new(f64::INFINITY, 0.0, f64::INFINITY),
+        );
+        let b = plane.transform(Transformation::translation(0.0, 3.0, 0.0).matrix());
+
+        assert_eq!(b.min().y(), 3.0);
+        assert_eq!(b.max().y(), 3.0);
+        assert_eq!(b.min().x(), f64::NEG_INFINITY);
+        assert_eq!(b.max().z(), f64::INFINITY);
+    }
+
+    #[test]
+    fn ray_intersects_bounds() {
+        let b = Bounds::new(Point3D::new(5.0, -2.0, 0.0), Point3D::new(11.0, 4.0, 7.0));
+        let cases = [
+            ((15.0, 1.0, 2.0), (-1.0, 0.0, 0.0), true),
+            ((-5.0, -1.0, 4.0), (1.0, 0.0, 0.0), true),
+            ((7.0, 6.0, 5.0), (0.0, -1.0, 0.0), true),
+            ((9.0, -5.0, 6.0), (0.0, 1.0, 0.0), true),
+            ((8.0, 2.0, 12.0), (0.0, 0.0, -1.0), true),
+            ((6.0, 0.0, -5.0), (0.0, 0.0, 1.0), true),
+            ((8.0, 1.0, 3.5), (0.0, 0.0, 1.0), true),
+            ((9.0, -1.0, -8.0), (2.0, 4.0, 6.0), false),
+            ((8.0, 3.0, -4.0), (6.0, 2.0, 4.0), false),
+            ((9.0, -1.0, -2.0), (4.0, 6.0, 2.0), false),
+            ((4.0, 0.0, 9.0), (0.0, 0.0, -1.0), false),
+            ((8.0, 6.0, -1.0), (0.0, -1.0, 0.0), false),
+            ((12.0, 5.0, 4.0), (-1.0, 0.0, 0.0), false),
+        ];
+
+        for (origin, direction, expected) in cases.iter() {
+            let r = Ray::new(
+                Point3D::new(origin.0, origin.1, origin.2),
+                Vector3D::new(direction.0, direction.1, direction.2).normalize(),
+            );
+
+            assert_eq!(b.intersects(&r), *expected, "{:?}", origin);
+        }
+        assert!(!Bounds::empty().intersects(&Ray::new(
+            Point3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(0.0, 0.0, 1.0)
+        )));
+    }
+}
@@ -0,0 +1,257 @@
+//! A bounding volume hierarchy, letting a ray skip over whole clusters
+//! of objects whose combined bounding box it misses.
+
+use crate::bounds::Bounds;
+use crate::raycasting::Ray;
+
+/// Nodes with this many objects or fewer aren't split any further.
+const MAX_LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Leaf {
+        bounds: Bounds,
+        objects: Vec<usize>,
+    },
+    Branch {
+        bounds: Bounds,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounds(&self) -> &Bounds {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Branch { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// A binary tree of boxes over a set of objects, built by repeatedly
+/// splitting them at the median along the axis where they are most
+/// spread out.
+///
+/// Objects with infinite bounds, like planes, would make every box
+/// above them infinite too, so they are kept aside and always visited.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    /// Builds a hierarchy over objects given as their id and bounds.
+    /// Objects with empty bounds can never be hit, so are left out.
+    pub fn build(objects: &[(usize, Bounds)]) -> Self {
+        let mut bvh = Bvh::default();
+        let mut bounded = Vec::new();
+        for (id, bounds) in objects.iter().filter(|(_, b)| !b.is_empty()) {
+            if bounds.is_finite() {
+                bounded.push((*id, *bounds));
+            } else {
+                bvh.unbounded.push(*id);
+            }
+        }
+
+        if !bounded.is_empty() {
+            bvh.split(&mut bounded);
+        }
+        bvh
+    }
+
+    /// Adds a node for `objects`, along with everything below it, and
+    /// returns its index. The root always ends up at index 0.
+    fn split(&mut self, objects: &mut [(usize, Bounds)]) -> usize {
+        let bounds = objects
+            .iter()
+            .fold(Bounds::empty(), |acc, (_, b)| acc.union(b));
+        let centroids = Bounds::from_points(
+            &objects
+                .iter()
+                .map(|(_, b)| b.centroid())
+                .collect::<Vec<_>>(),
+        );
+        let extent = centroids.max() - centroids.min();
+        let extents = [extent.x(), extent.y(), extent.z()];
+        let (axis, widest) =
+            extents.iter().enumerate().fold(
+                (0, 0.0),
+                |acc, (axis, e)| if *e > acc.1 { (axis, *e) } else { acc },
+            );
+
+        // Objects which are all centred on the same point can't be
+        // told apart by splitting, so they share a leaf.
+        let idx = self.nodes.len();
+        if objects.len() <= MAX_LEAF_SIZE || widest == 0.0 {
+            self.nodes.push(Node::Leaf {
+                bounds,
+                objects: objects.iter().map(|(id, _)| *id).collect(),
+            });
+            return idx;
+        }
+
+        let mid = objects.len() / 2;
+        objects.select_nth_unstable_by(mid, |(_, a), (_, b)| {
+            let (a, b) = (a.centroid(), b.centroid());
+            let (a, b) = match axis {
+                0 => (a.x(), b.x()),
+                1 => (a.y(), b.y()),
+                _ => (a.z(), b.z()),
+            };
+            a.total_cmp(&b)
+        });
+
+        // Reserve this node's slot before its children take theirs.
+        self.nodes.push(Node::Leaf {
+            bounds,
+            objects: Vec::new(),
+        });
+        let (lower, upper) = objects.split_at_mut(mid);
+        let left = self.split(lower);
+        let right = self.split(upper);
+        self.nodes[idx] = Node::Branch {
+            bounds,
+            left,
+            right,
+        };
+        idx
+    }
+
+    /// The bounds of everything in the hierarchy apart from unbounded
+    /// objects.
+    pub fn bounds(&self) -> Bounds {
+        self.nodes
+            .first()
+            .map(|n| *n.bounds())
+            .unwrap_or_else(Bounds::empty)
+    }
+
+    /// Calls `visit` with the id of every object the ray might hit.
+    /// Objects in boxes the ray misses entirely are skipped.
+    pub fn candidates<F: FnMut(usize)>(&self, ray: &Ray, mut visit: F) {
+        for id in &self.unbounded {
+            visit(*id);
+        }
+
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            if !node.bounds().intersects(ray) {
+                continue;
+            }
+            match node {
+                Node::Leaf { objects, .. } => objects.iter().for_each(|id| visit(*id)),
+                Node::Branch { left, right, .. } => {
+                    stack.push(*right);
+                    stack.push(*left);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::descartes::{Point3D, Vector3D};
+
+    /// Unit boxes centred at each whole number along the x axis.
+    fn row_of_boxes(count: usize) -> Vec<(usize, Bounds)> {
+        (0..count)
+            .map(|i| {
+                let x = i as f64;
+                (
+                    i,
+                    Bounds::new(
+                        Point3D::new(x - 0.5, -0.5, -0.5),
+                        Point3D::new(x + 0.5, 0.5, 0.5),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    fn candidates(bvh: &Bvh, ray: &Ray) -> Vec<usize> {
+        let mut ids = Vec::new();
+        bvh.candidates(ray, |id| ids.push(id));
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn empty_hierarchy() {
+        let bvh = Bvh::build(&[]);
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert!(bvh.bounds().is_empty());
+        assert!(candidates(&bvh, &r).is_empty());
+    }
+
+    #[test]
+    fn small_sets_are_one_leaf() {
+        let bvh = Bvh::build(&row_of_boxes(MAX_LEAF_SIZE));
+
+        assert_eq!(bvh.nodes.len(), 1);
+    }
+
+    #[test]
+    fn large_sets_are_split() {
+        let bvh = Bvh::build(&row_of_boxes(100));
+
+        assert!(bvh.nodes.len() > 1);
+        assert_eq!(bvh.bounds().min(), Point3D::new(-0.5, -0.5, -0.5));
+        assert_eq!(bvh.bounds().max(), Point3D::new(99.5, 0.5, 0.5));
+        for node in &bvh.nodes {
+            if let Node::Leaf { objects, .. } = node {
+                assert!(objects.len() <= MAX_LEAF_SIZE);
+            }
+        }
+    }
+
+    #[test]
+    fn only_nearby_objects_are_visited() {
+        let bvh = Bvh::build(&row_of_boxes(100));
+        let r = Ray::new(Point3D::new(42.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let ids = candidates(&bvh, &r);
+
+        assert!(ids.contains(&42));
+        assert!(ids.len() <= MAX_LEAF_SIZE * 2);
+    }
+
+    #[test]
+    fn ray_along_every_object() {
+        let bvh = Bvh::build(&row_of_boxes(100));
+        let r = Ray::new(Point3D::new(-5.0, 0.0, 0.0), Vector3D::new(1.0, 0.0, 0.0));
+
+        assert_eq!(candidates(&bvh, &r), (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unbounded_objects_always_visited() {
+        let mut objects = row_of_boxes(10);
+        objects.push((
+            10,
+            Bounds::new(
+                Point3D::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+                Point3D::new(f64::INFINITY, 0.0, f64::INFINITY),
+            ),
+        ));
+        objects.push((11, Bounds::empty()));
+        let bvh = Bvh::build(&objects);
+        let r = Ray::new(Point3D::new(0.0, 5.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert_eq!(candidates(&bvh, &r), vec![10]);
+    }
+
+    #[test]
+    fn coincident_objects_share_a_leaf() {
+        let objects: Vec<(usize, Bounds)> = (0..10).map(|i| (i, row_of_boxes(1)[0].1)).collect();
+        let bvh = Bvh::build(&objects);
+
+        assert_eq!(bvh.nodes.len(), 1);
+    }
+}
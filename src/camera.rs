@@ -0,0 +1,200 @@
+use crate::canvas::Canvas;
+use crate::descartes::Point3D;
+use crate::matrix::M4;
+use crate::raycasting::Ray;
+use crate::transformations::Transformation;
+use crate::world::{World, DEFAULT_DEPTH};
+
+/// Maps the pixels of a canvas onto rays cast into a world. The canvas
+/// is imagined one unit in front of the camera.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    hsize: usize,
+    vsize: usize,
+    field_of_view: f64,
+    transform: Transformation,
+    inverse: M4,
+    half_width: f64,
+    half_height: f64,
+    pixel_size: f64,
+    depth: usize,
+}
+
+impl Camera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        let half_view = (field_of_view / 2.0).tan();
+        let aspect = hsize as f64 / vsize as f64;
+        let (half_width, half_height) = if aspect >= 1.0 {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        };
+
+        Camera {
+            hsize,
+            vsize,
+            field_of_view,
+            transform: Transformation::identity(),
+            inverse: M4::identity(),
+            half_width,
+            half_height,
+            pixel_size: (half_width * 2.0) / hsize as f64,
+            depth: DEFAULT_DEPTH,
+        }
+    }
+
+    pub fn hsize(&self) -> usize {
+        self.hsize
+    }
+
+    pub fn vsize(&self) -> usize {
+        self.vsize
+    }
+
+    pub fn field_of_view(&self) -> f64 {
+        self.field_of_view
+    }
+
+    pub fn pixel_size(&self) -> f64 {
+        self.pixel_size
+    }
+
+    /// How many times each ray may bounce off reflective surfaces.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth
+    }
+
+    pub fn transform(&self) -> &Transformation {
+        &self.transform
+    }
+
+    /// Panics if the transformation cannot be inverted.
+    pub fn set_transform(&mut self, transform: Transformation) {
+        self.inverse = transform
+            .matrix()
+            .inverse()
+            .expect("camera transformations must be invertible");
+        self.transform = transform;
+    }
+
+    pub fn ray_for_pixel(&self, x: usize, y: usize) -> Ray {
+        // Offsets are to the centre of the pixel.
+        let xoffset = (x as f64 + 0.5) * self.pixel_size;
+        let yoffset = (y as f64 + 0.5) * self.pixel_size;
+
+        // The camera looks toward -z, so +x is to the *left*.
+        let world_x = self.half_width - xoffset;
+        let world_y = self.half_height - yoffset;
+
+        let pixel = self.inverse * Point3D::new(world_x, world_y, -1.0);
+        let origin = self.inverse * Point3D::new(0.0, 0.0, 0.0);
+
+        Ray::new(origin, (pixel - origin).normalize())
+    }
+
+    pub fn render(&self, world: &World) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let ray = self.ray_for_pixel(x, y);
+                image.write_pixel(x, y, world.colour_at(&ray, self.depth));
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::colour::Colour;
+    use crate::descartes::Vector3D;
+    use crate::lighting::PointLight;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn construct_camera() {
+        let c = Camera::new(160, 120, PI / 2.0);
+
+        assert_eq!(c.hsize(), 160);
+        assert_eq!(c.vsize(), 120);
+        assert_eq!(c.depth(), DEFAULT_DEPTH);
+        assert_eq!(c.field_of_view(), PI / 2.0);
+        assert_eq!(c.transform(), &Transformation::identity());
+    }
+
+    #[test]
+    fn horizontal_pixel_size() {
+        let c = Camera::new(200, 125, PI / 2.0);
+
+        assert!((c.pixel_size() - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn vertical_pixel_size() {
+        let c = Camera::new(125, 200, PI / 2.0);
+
+        assert!((c.pixel_size() - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ray_through_centre() {
+        let c = Camera::new(201, 101, PI / 2.0);
+        let r = c.ray_for_pixel(100, 50);
+
+        assert_eq!(r.origin(), Point3D::new(0.0, 0.0, 0.0));
+        assert_eq!(r.direction(), Vector3D::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn ray_through_corner() {
+        let c = Camera::new(201, 101, PI / 2.0);
+        let r = c.ray_for_pixel(0, 0);
+
+        assert_eq!(r.origin(), Point3D::new(0.0, 0.0, 0.0));
+        assert_eq!(r.direction(), Vector3D::new(0.66519, 0.33259, -0.66851));
+    }
+
+    #[test]
+    fn ray_from_transformed_camera() {
+        let mut c = Camera::new(201, 101, PI / 2.0);
+        c.set_transform(
+            Transformation::rotation_y(PI / 4.0) * Transformation::translation(0.0, -2.0, 5.0),
+        );
+        let r = c.ray_for_pixel(100, 50);
+        let k = 2.0_f64.sqrt() / 2.0;
+
+        assert_eq!(r.origin(), Point3D::new(0.0, 2.0, -5.0));
+        assert_eq!(r.direction(), Vector3D::new(k, 0.0, -k));
+    }
+
+    #[test]
+    fn render_world() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Point3D::new(-10.0, 10.0, -10.0),
+            Colour::white(),
+        ));
+        let outer = w.sphere();
+        let m = outer.material_mut();
+        m.set_colour(Colour::new(0.8, 1.0, 0.6));
+        m.set_diffuse(0.7);
+        m.set_specular(0.2);
+        w.sphere()
+            .set_transform(Transformation::scaling(0.5, 0.5, 0.5));
+
+        let mut c = Camera::new(11, 11, PI / 2.0);
+        c.set_transform(Transformation::view_transform(
+            Point3D::new(0.0, 0.0, -5.0),
+            Point3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        ));
+        let image = c.render(&w);
+
+        assert_eq!(image.get_pixel(5, 5), Colour::new(0.38066, 0.47583, 0.2855));
+    }
+}
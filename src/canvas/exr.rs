@@ -0,0 +1,196 @@
+//! OpenEXR output: a single part scanline image with 32 bit float red,
+//! green and blue channels and no compression, which every OpenEXR
+//! reader understands.
+
+use super::Canvas;
+use crate::colour::Colour;
+use crate::error::Error;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::path::Path;
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+
+/// Version 2, with none of the flags for tiles, long names or multiple
+/// parts set.
+const VERSION: u32 = 2;
+
+const FLOAT: i32 = 2;
+
+impl Canvas {
+    /// Writes the image to a file, creating or truncating it.
+    pub fn save_exr<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        self.write_exr(std::io::BufWriter::new(file))
+    }
+
+    /// Streams the image out a scanline at a time. Nothing is clamped, so
+    /// the full range of the render survives for tone mapping later.
+    pub fn write_exr<W: Write>(&self, mut w: W) -> Result<(), Error> {
+        let too_large = || Error::image("too large for OpenEXR");
+        let width = i32::try_from(self.width).map_err(|_| too_large())?;
+        let height = i32::try_from(self.height()).map_err(|_| too_large())?;
+        let line_size = i32::try_from(self.width * 3 * 4).map_err(|_| too_large())?;
+
+        let mut header = Vec::new();
+        header.extend(MAGIC);
+        header.extend(VERSION.to_le_bytes());
+
+        // Channels are listed alphabetically, and their values are
+        // stored in the same order.
+        let mut channels = Vec::new();
+        for name in [b'B', b'G', b'R'] {
+            channels.extend([name, 0]);
+            channels.extend(FLOAT.to_le_bytes());
+            // Perceptually linear flag and three reserved bytes.
+            channels.extend([0; 4]);
+            // Sampled at every pixel in x and y.
+            channels.extend(1_i32.to_le_bytes());
+            channels.extend(1_i32.to_le_bytes());
+        }
+        channels.push(0);
+
+        let mut window = Vec::with_capacity(16);
+        for corner in [0, 0, width - 1, height - 1] {
+            window.extend(corner.to_le_bytes());
+        }
+
+        attribute(&mut header, "channels", "chlist", &channels);
+        attribute(&mut header, "compression", "compression", &[0]);
+        attribute(&mut header, "dataWindow", "box2i", &window);
+        attribute(&mut header, "displayWindow", "box2i", &window);
+        attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+        attribute(
+            &mut header,
+            "pixelAspectRatio",
+            "float",
+            &1.0_f32.to_le_bytes(),
+        );
+        attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+        attribute(
+            &mut header,
+            "screenWindowWidth",
+            "float",
+            &1.0_f32.to_le_bytes(),
+        );
+        header.push(0);
+
+        // The header is followed by the file offset of every scanline,
+        // each of which is its y, its size, then the pixels.
+        let block_size = 8 + line_size as u64;
+        let first_block = (header.len() + 8 * self.height()) as u64;
+        w.write_all(&header)?;
+        for y in 0..self.height() as u64 {
+            w.write_all(&(first_block + y * block_size).to_le_bytes())?;
+        }
+
+        let channels: [fn(&Colour) -> f64; 3] = [Colour::blue, Colour::green, Colour::red];
+        let mut block = Vec::with_capacity(block_size as usize);
+        for (y, pixels) in self.pixels.chunks(self.width).enumerate() {
+            block.clear();
+            block.extend((y as i32).to_le_bytes());
+            block.extend(line_size.to_le_bytes());
+            for channel in channels.iter() {
+                for pixel in pixels {
+                    block.extend((channel(pixel) as f32).to_le_bytes());
+                }
+            }
+            w.write_all(&block)?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    for s in [name, kind] {
+        header.extend(s.as_bytes());
+        header.push(0);
+    }
+    header.extend((value.len() as i32).to_le_bytes());
+    header.extend(value);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn le_i32(bytes: &[u8]) -> i32 {
+        i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    fn le_f32(bytes: &[u8]) -> f32 {
+        f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    /// The attributes as name, type and value, and where the header ends.
+    fn attributes(data: &[u8]) -> (Vec<(String, String, Vec<u8>)>, usize) {
+        let mut pos = 8;
+        let mut attributes = Vec::new();
+        let string = |pos: &mut usize| {
+            let end = *pos + data[*pos..].iter().position(|&b| b == 0).unwrap();
+            let s = String::from_utf8(data[*pos..end].to_vec()).unwrap();
+            *pos = end + 1;
+            s
+        };
+        loop {
+            let name = string(&mut pos);
+            if name.is_empty() {
+                return (attributes, pos);
+            }
+            let kind = string(&mut pos);
+            let size = le_i32(&data[pos..]) as usize;
+            attributes.push((name, kind, data[pos + 4..pos + 4 + size].to_vec()));
+            pos += 4 + size;
+        }
+    }
+
+    #[test]
+    fn header() {
+        let mut out = Vec::new();
+        Canvas::new(3, 2).write_exr(&mut out).unwrap();
+        let (attributes, _) = attributes(&out);
+
+        assert_eq!(out[..8], [0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+        assert_eq!(
+            attributes
+                .iter()
+                .map(|(name, kind, _)| format!("{}:{}", name, kind))
+                .collect::<Vec<_>>(),
+            [
+                "channels:chlist",
+                "compression:compression",
+                "dataWindow:box2i",
+                "displayWindow:box2i",
+                "lineOrder:lineOrder",
+                "pixelAspectRatio:float",
+                "screenWindowCenter:v2f",
+                "screenWindowWidth:float",
+            ]
+        );
+        let window: Vec<i32> = attributes[2].2.chunks(4).map(le_i32).collect();
+        assert_eq!(window, [0, 0, 2, 1]);
+        assert_eq!(attributes[0].2.len(), 3 * 18 + 1);
+    }
+
+    #[test]
+    fn scanlines_keep_full_range() {
+        let mut c = Canvas::new(2, 3);
+        c.write_pixel(1, 2, Colour::new(12.5, -0.25, 0.125));
+        let mut out = Vec::new();
+        c.write_exr(&mut out).unwrap();
+        let (_, end) = attributes(&out);
+
+        let offsets: Vec<usize> = out[end..end + 24]
+            .chunks(8)
+            .map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as usize)
+            .collect();
+        assert_eq!(offsets, [end + 24, end + 56, end + 88]);
+        assert_eq!(out.len(), end + 120);
+
+        let block = &out[offsets[2]..];
+        assert_eq!((le_i32(block), le_i32(&block[4..])), (2, 24));
+        let values: Vec<f32> = block[8..32].chunks(4).map(le_f32).collect();
+        assert_eq!(values, [0.0, 0.125, 0.0, -0.25, 0.0, 12.5]);
+    }
+}
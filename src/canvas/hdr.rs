@@ -0,0 +1,433 @@
+//! Radiance RGBE images, which keep the full range of a render: each
+//! pixel is three 8 bit mantissas sharing an exponent.
+
+use super::Canvas;
+use crate::colour::Colour;
+use crate::error::Error;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const FORMAT: &str = "32-bit_rle_rgbe";
+
+/// Repeats shorter than this are cheaper to store as literal bytes.
+const MIN_RUN: usize = 4;
+
+/// Run-length encoded scanlines can only be this wide or narrower, and
+/// aren't worth it below the minimum.
+const RLE_WIDTHS: std::ops::RangeInclusive<usize> = 8..=0x7fff;
+
+impl Canvas {
+    pub fn open_hdr<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Canvas::read_hdr(file)
+    }
+
+    /// Reads flat or run-length encoded RGBE images in the standard
+    /// top-to-bottom orientation. Any `EXPOSURE` in the header is
+    /// divided out, giving back the original radiance.
+    pub fn read_hdr<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut data = &data[..];
+
+        let (width, height, exposure) = read_header(&mut data)?;
+        let needed = width
+            .checked_mul(height)
+            .and_then(|_| height.checked_mul(min_scanline_bytes(width)))
+            .ok_or_else(|| Error::image("image is too large"))?;
+        // A header claiming more than the data could hold is caught
+        // before allocating for it.
+        if data.len() < needed {
+            return Err(Error::image("image data ends early"));
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        let mut scanline = Vec::with_capacity(width);
+        for row in canvas.pixels.chunks_mut(width) {
+            read_scanline(&mut data, width, &mut scanline)?;
+            for (pixel, rgbe) in row.iter_mut().zip(scanline.iter()) {
+                *pixel = from_rgbe(*rgbe) * (1.0 / exposure);
+            }
+        }
+        Ok(canvas)
+    }
+
+    /// Writes the image to a file, creating or truncating it.
+    pub fn save_hdr<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        self.write_hdr(std::io::BufWriter::new(file))
+    }
+
+    /// Streams the image out a row at a time, run-length encoding each
+    /// row when it is wide enough. Negative channels are written as zero.
+    pub fn write_hdr<W: Write>(&self, mut w: W) -> Result<(), Error> {
+        write!(
+            w,
+            "#?RADIANCE\nFORMAT={}\n\n-Y {} +X {}\n",
+            FORMAT,
+            self.height(),
+            self.width
+        )?;
+
+        let mut row = Vec::with_capacity(self.width * 4);
+        for pixels in self.pixels.chunks(self.width) {
+            row.clear();
+            if RLE_WIDTHS.contains(&self.width) {
+                row.extend([2, 2, (self.width >> 8) as u8, self.width as u8]);
+                let rgbe: Vec<[u8; 4]> = pixels.iter().map(|p| to_rgbe(*p)).collect();
+                for component in 0..4 {
+                    let bytes: Vec<u8> = rgbe.iter().map(|p| p[component]).collect();
+                    write_rle(&mut row, &bytes);
+                }
+            } else {
+                for pixel in pixels {
+                    row.extend(to_rgbe(*pixel));
+                }
+            }
+            w.write_all(&row)?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+}
+
+/// Scales the brightest channel to a mantissa between 128 and 255 and
+/// stores the power of two it took. Dimmer channels lose precision
+/// relative to it. Channels which are NaN are written as black, and
+/// any too bright for the format, infinity included, as the brightest
+/// value it holds.
+fn to_rgbe(colour: Colour) -> [u8; 4] {
+    let (r, g, b) = (
+        colour.red().max(0.0),
+        colour.green().max(0.0),
+        colour.blue().max(0.0),
+    );
+    let max = r.max(g).max(b);
+    if max < 1e-38 {
+        return [0; 4];
+    }
+
+    // The brightest channel is in [2^(exponent - 1), 2^exponent).
+    let exponent = (max.log2().floor() + 1.0).min(127.0) as i32;
+    let scale = 256.0 / 2.0_f64.powi(exponent);
+    let mantissa = |channel: f64| (channel * scale + 0.5).min(255.0) as u8;
+    [
+        mantissa(r),
+        mantissa(g),
+        mantissa(b),
+        (exponent + 128) as u8,
+    ]
+}
+
+fn from_rgbe([r, g, b, e]: [u8; 4]) -> Colour {
+    if e == 0 {
+        return Colour::black();
+    }
+    let scale = 2.0_f64.powi(e as i32 - 128 - 8);
+    Colour::new(r as f64 * scale, g as f64 * scale, b as f64 * scale)
+}
+
+/// Appends one component of a scanline as runs, a byte of 128 plus the
+/// length followed by the repeated byte, and literals, a byte of the
+/// length followed by that many bytes.
+fn write_rle(out: &mut Vec<u8>, data: &[u8]) {
+    let run_at = |i: usize| {
+        data[i..]
+            .iter()
+            .take(127)
+            .take_while(|&&b| b == data[i])
+            .count()
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let run = run_at(i);
+        if run >= MIN_RUN {
+            out.extend([128 + run as u8, data[i]]);
+            i += run;
+            continue;
+        }
+
+        let start = i;
+        while i < data.len() && i - start < 128 && (i == start || run_at(i) < MIN_RUN) {
+            i += 1;
+        }
+        out.push((i - start) as u8);
+        out.extend_from_slice(&data[start..i]);
+    }
+}
+
+/// Parses the header lines and resolution, returning the width, height
+/// and overall exposure.
+fn read_header(data: &mut &[u8]) -> Result<(usize, usize, f64), Error> {
+    let mut next_line = || -> Result<String, Error> {
+        let end = data
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| Error::image("header ends early"))?;
+        let line = String::from_utf8_lossy(&data[..end]).into_owned();
+        *data = &data[end + 1..];
+        Ok(line)
+    };
+
+    if !next_line()?.starts_with("#?") {
+        return Err(Error::image("not a Radiance HDR file"));
+    }
+    let mut exposure = 1.0;
+    loop {
+        let line = next_line()?;
+        if line.is_empty() {
+            break;
+        } else if let Some(format) = line.strip_prefix("FORMAT=") {
+            if format.trim() != FORMAT {
+                return Err(Error::image(format!("unsupported format {:?}", format)));
+            }
+        } else if let Some(value) = line.strip_prefix("EXPOSURE=") {
+            match value.trim().parse::<f64>() {
+                Ok(factor) if factor.is_finite() && factor > 0.0 => exposure *= factor,
+                _ => return Err(Error::image(format!("invalid exposure {:?}", value))),
+            }
+        }
+    }
+    // Several exposures multiply together, which could still overflow.
+    if !exposure.is_normal() {
+        return Err(Error::image("exposure is out of range"));
+    }
+
+    let resolution = next_line()?;
+    match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => {
+            let dimension = |s: &str| match s.parse::<usize>() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(Error::image(format!("invalid resolution {:?}", resolution))),
+            };
+            Ok((dimension(width)?, dimension(height)?, exposure))
+        }
+        _ => Err(Error::image(format!(
+            "unsupported orientation {:?}",
+            resolution
+        ))),
+    }
+}
+
+fn take<'a>(data: &mut &'a [u8], count: usize) -> Result<&'a [u8], Error> {
+    if data.len() < count {
+        return Err(Error::image("image data ends early"));
+    }
+    let (taken, rest) = data.split_at(count);
+    *data = rest;
+    Ok(taken)
+}
+
+fn read_scanline(data: &mut &[u8], width: usize, out: &mut Vec<[u8; 4]>) -> Result<(), Error> {
+    out.clear();
+    match data.get(..4) {
+        Some(&[2, 2, hi, lo]) if RLE_WIDTHS.contains(&width) && hi < 128 => {
+            if ((hi as usize) << 8 | lo as usize) != width {
+                return Err(Error::image("scanline width doesn't match the image"));
+            }
+            *data = &data[4..];
+            read_rle_scanline(data, width, out)
+        }
+        _ => read_flat_scanline(data, width, out),
+    }
+}
+
+/// Each component is stored in turn, as runs and literals.
+fn read_rle_scanline(data: &mut &[u8], width: usize, out: &mut Vec<[u8; 4]>) -> Result<(), Error> {
+    out.resize(width, [0; 4]);
+    for component in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = take(data, 1)?[0] as usize;
+            let (length, run) = if count > 128 {
+                (count - 128, true)
+            } else {
+                (count, false)
+            };
+            if length == 0 || x + length > width {
+                return Err(Error::image("bad run length"));
+            }
+
+            if run {
+                let byte = take(data, 1)?[0];
+                out[x..x + length]
+                    .iter_mut()
+                    .for_each(|p| p[component] = byte);
+            } else {
+                let bytes = take(data, length)?;
+                for (p, &byte) in out[x..x + length].iter_mut().zip(bytes) {
+                    p[component] = byte;
+                }
+            }
+            x += length;
+        }
+    }
+    Ok(())
+}
+
+/// Plain RGBE pixels, where an old style run of up to 255 copies of the
+/// previous pixel is marked by 1, 1, 1 and the count.
+fn read_flat_scanline(data: &mut &[u8], width: usize, out: &mut Vec<[u8; 4]>) -> Result<(), Error> {
+    while out.len() < width {
+        let pixel = take(data, 4)?;
+        if pixel[..3] == [1, 1, 1] {
+            let previous = *out
+                .last()
+                .ok_or_else(|| Error::image("run at the start of a scanline"))?;
+            let count = pixel[3] as usize;
+            if out.len() + count > width {
+                return Err(Error::image("bad run length"));
+            }
+            out.resize(out.len() + count, previous);
+        } else {
+            out.push([pixel[0], pixel[1], pixel[2], pixel[3]]);
+        }
+    }
+    Ok(())
+}
+
+/// The fewest bytes a scanline can be stored in. Flat scanlines need a
+/// pixel then a run for every 255 more. Run-length encoded ones need
+/// their marker and, for each component, a two byte run for every 127
+/// pixels; they may still be flat, so the smaller of the two counts.
+fn min_scanline_bytes(width: usize) -> usize {
+    let flat = 4 + 4 * (width - 1).div_ceil(255);
+    if RLE_WIDTHS.contains(&width) {
+        flat.min(4 + 4 * 2 * width.div_ceil(127))
+    } else {
+        flat
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// RGBE keeps about two significant figures relative to the
+    /// brightest channel.
+    fn assert_close(actual: Colour, expected: Colour) {
+        let max = expected.red().max(expected.green()).max(expected.blue());
+        let error = (actual.red() - expected.red())
+            .abs()
+            .max((actual.green() - expected.green()).abs())
+            .max((actual.blue() - expected.blue()).abs());
+        assert!(error <= max / 256.0, "{:?} != {:?}", actual, expected);
+    }
+
+    fn encode(c: &Canvas) -> Vec<u8> {
+        let mut out = Vec::new();
+        c.write_hdr(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn rgbe_encoding() {
+        assert_eq!(to_rgbe(Colour::new(1.0, 0.5, 0.25)), [128, 64, 32, 129]);
+        assert_eq!(to_rgbe(Colour::new(12.0, 0.0, 3.0)), [192, 0, 48, 132]);
+        assert_eq!(to_rgbe(Colour::new(-1.0, 0.0, 0.0)), [0; 4]);
+        assert_eq!(
+            to_rgbe(Colour::new(f64::INFINITY, 1.0, 0.0)),
+            [255, 0, 0, 255]
+        );
+        assert_eq!(to_rgbe(Colour::new(1e300, 0.0, 0.0)), [255, 0, 0, 255]);
+        assert_eq!(to_rgbe(Colour::new(f64::NAN, 0.5, 0.0)), [0, 128, 0, 128]);
+        assert_eq!(to_rgbe(Colour::new(f64::NAN, 0.0, 0.0)), [0; 4]);
+        assert_eq!(from_rgbe([128, 64, 32, 129]), Colour::new(1.0, 0.5, 0.25));
+        assert_eq!(from_rgbe([255, 255, 255, 0]), Colour::black());
+    }
+
+    #[test]
+    fn header() {
+        let out = encode(&Canvas::new(3, 2));
+
+        assert!(out.starts_with(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 3\n"));
+    }
+
+    #[test]
+    fn round_trips_beyond_white() {
+        for width in [3, 40] {
+            let mut c = Canvas::new(width, 2);
+            for x in 0..width {
+                c.write_pixel(x, 0, Colour::new(x as f64 * 10.0, 0.5, 0.001));
+                c.write_pixel(x, 1, Colour::new(1.0, 1.0, 1.0));
+            }
+            let read = Canvas::read_hdr(&encode(&c)[..]).unwrap();
+
+            assert_eq!((read.width(), read.height()), (width, 2));
+            for (a, b) in read.pixels.iter().zip(c.pixels.iter()) {
+                assert_close(*a, *b);
+            }
+        }
+    }
+
+    #[test]
+    fn run_length_encoding() {
+        let data = [7, 7, 7, 7, 7, 1, 2, 3, 9, 9, 9, 9, 4, 4];
+        let mut out = Vec::new();
+        write_rle(&mut out, &data);
+
+        assert_eq!(out, [133, 7, 3, 1, 2, 3, 132, 9, 2, 4, 4]);
+    }
+
+    #[test]
+    fn long_runs_are_split() {
+        let data = [5; 300];
+        let mut out = Vec::new();
+        write_rle(&mut out, &data);
+
+        assert_eq!(out, [255, 5, 255, 5, 128 + 46, 5]);
+    }
+
+    #[test]
+    fn read_old_style_runs() {
+        let mut data = b"#?RGBE\nEXPOSURE=2\n\n-Y 1 +X 4\n".to_vec();
+        data.extend([128, 64, 32, 130, 1, 1, 1, 2, 0, 0, 128, 129]);
+        let c = Canvas::read_hdr(&data[..]).unwrap();
+
+        assert_eq!(c.get_pixel(0, 0), Colour::new(1.0, 0.5, 0.25));
+        assert_eq!(c.get_pixel(2, 0), Colour::new(1.0, 0.5, 0.25));
+        assert_eq!(c.get_pixel(3, 0), Colour::new(0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn invalid_exposure() {
+        for exposure in ["0", "-2", "inf", "NaN", "bright"] {
+            let data = format!("#?RADIANCE\nEXPOSURE={}\n\n-Y 1 +X 1\n@@@A", exposure);
+
+            assert!(matches!(
+                Canvas::read_hdr(data.as_bytes()),
+                Err(Error::Image(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn unsupported_format() {
+        let data = b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n\0\0\0\0";
+
+        assert!(matches!(Canvas::read_hdr(&data[..]), Err(Error::Image(_))));
+    }
+
+    #[test]
+    fn huge_header_on_short_file() {
+        for resolution in ["-Y 4000000 +X 4000000", "-Y 18446744073709551615 +X 2"] {
+            let data = format!("#?RADIANCE\n\n{}\n\0\0\0\0", resolution);
+
+            assert!(matches!(
+                Canvas::read_hdr(data.as_bytes()),
+                Err(Error::Image(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let out = encode(&Canvas::white(10, 3));
+
+        assert!(matches!(
+            Canvas::read_hdr(&out[..out.len() - 1]),
+            Err(Error::Image(_))
+        ));
+    }
+}
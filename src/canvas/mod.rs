@@ -1,5 +1,14 @@
 use crate::colour::Colour;
 
+mod exr;
+mod hdr;
+mod netpbm;
+mod png;
+
+pub use netpbm::PpmFormat;
+pub use png::PngFormat;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Canvas {
     width: usize,
     pixels: Vec<Colour>,
@@ -49,6 +58,10 @@ impl Canvas {
         });
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
     pub fn height(&self) -> usize {
         self.pixels.len() / self.width
     }
@@ -57,16 +70,6 @@ impl Canvas {
         self.pixels[(y * self.width) + x]
     }
 
-    fn u8_pixels(&self) -> Vec<u8> {
-        let mut v = Vec::with_capacity(self.pixels.len());
-        for pixel in &self.pixels {
-            v.push(pixel.red_u8());
-            v.push(pixel.green_u8());
-            v.push(pixel.blue_u8());
-        }
-        v
-    }
-
     pub fn u8_with_alpha(&self, alpha: u8) -> Vec<u8> {
         let mut v = Vec::with_capacity((self.pixels.len() / 3) * 4);
         for pixel in &self.pixels {
@@ -77,39 +80,6 @@ impl Canvas {
         }
         v
     }
-
-    pub fn to_ppm(self, filename: &str) -> () {
-        // TODO: in theory this should be capped at 70 characters per
-        // line, including spaces. Most modern implementations don't
-        // care, so ignoring this for now.
-        use std::fs::File;
-        use std::io::prelude::*;
-
-        // This is stupidly inefficient; better done as bytes?
-        let pixels = self
-            .u8_pixels()
-            .chunks(self.width)
-            .map(|line| {
-                line.iter()
-                    .map(|px| px.to_string())
-                    .collect::<Vec<String>>()
-                    .join(" ")
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
-
-        // The header for this file type consists of an identifier, the width/height data,
-        // and then space-separated integers for red, green and blue channels.
-        let content = format!(
-            "P3\n{} {}\n255\n{}\n",
-            self.width,
-            self.pixels.len() / self.width,
-            pixels
-        );
-
-        let mut output_file = File::create(filename).unwrap();
-        output_file.write_all(&content.as_bytes()).unwrap();
-    }
 }
 
 #[cfg(test)]
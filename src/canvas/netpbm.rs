@@ -0,0 +1,663 @@
+//! The Netpbm family of images: PBM, PGM and PPM in their plain and raw
+//! flavours, and PAM.
+
+use super::Canvas;
+use crate::colour::Colour;
+use crate::error::Error;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// The flavours of PPM which can be written: plain text, or raw bytes
+/// which are a fraction of the size and much quicker to write.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PpmFormat {
+    Ascii,
+    Binary,
+}
+
+impl Canvas {
+    pub fn open_ppm<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Canvas::read_ppm(file)
+    }
+
+    /// Reads any Netpbm image: plain or raw PBM, PGM and PPM (P1 to P6)
+    /// and PAM (P7). Channels are scaled by the file's maximum value, so
+    /// any bit depth becomes 0.0 to 1.0. Grey images become grey colours
+    /// and alpha channels are dropped.
+    pub fn read_ppm<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Parser::new(&data).canvas()
+    }
+
+    /// Writes the image to a file, creating or truncating it.
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P, format: PpmFormat) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        self.write_ppm(std::io::BufWriter::new(file), format)
+    }
+
+    /// Streams the image out a row at a time, so memory use doesn't grow
+    /// with the size of the image.
+    pub fn write_ppm<W: Write>(&self, mut w: W, format: PpmFormat) -> Result<(), Error> {
+        let magic = match format {
+            PpmFormat::Ascii => "P3",
+            PpmFormat::Binary => "P6",
+        };
+        write!(w, "{}\n{} {}\n255\n", magic, self.width, self.height())?;
+
+        let mut row = Vec::with_capacity(self.width * 12);
+        for pixels in self.pixels.chunks(self.width) {
+            row.clear();
+            match format {
+                PpmFormat::Ascii => write_ascii_row(&mut row, pixels),
+                PpmFormat::Binary => {
+                    for pixel in pixels {
+                        row.extend([pixel.red_u8(), pixel.green_u8(), pixel.blue_u8()]);
+                    }
+                }
+            }
+            w.write_all(&row)?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+}
+
+/// Plain PPM lines shouldn't be longer than 70 characters, so a row of
+/// pixels is wrapped onto as many lines as it needs. Every row still
+/// starts on a new line.
+fn write_ascii_row(out: &mut Vec<u8>, pixels: &[Colour]) {
+    const MAX_LINE: usize = 70;
+    let mut line_len = 0;
+
+    for pixel in pixels {
+        for channel in [pixel.red_u8(), pixel.green_u8(), pixel.blue_u8()] {
+            let value = channel.to_string();
+            if line_len > 0 && line_len + 1 + value.len() > MAX_LINE {
+                out.push(b'\n');
+                line_len = 0;
+            }
+            if line_len > 0 {
+                out.push(b' ');
+                line_len += 1;
+            }
+            out.extend_from_slice(value.as_bytes());
+            line_len += value.len();
+        }
+    }
+    out.push(b'\n');
+}
+
+/// How the samples after the header are stored.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Raster {
+    /// P1: a `0` or `1` per pixel, where 1 is black. Whitespace between
+    /// them is optional.
+    PlainBits,
+    /// P4: eight pixels to a byte, each row padded to a whole byte.
+    PackedBits,
+    /// P2, P3: whitespace separated decimal numbers.
+    Plain,
+    /// P5, P6, P7: one byte per sample, or two big-endian bytes if the
+    /// maximum value needs them.
+    Raw,
+}
+
+#[derive(Debug)]
+struct Header {
+    width: usize,
+    height: usize,
+    depth: usize,
+    maxval: u32,
+    raster: Raster,
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    line: usize,
+    /// The line of the most recent header value or sample, which is what
+    /// errors point at.
+    token_line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Parser {
+            data,
+            pos: 0,
+            line: 1,
+            token_line: 1,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::parse(self.token_line, message)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        if byte == b'\n' {
+            self.line += 1;
+        }
+        Some(byte)
+    }
+
+    /// Skips whitespace and comments, which run from `#` to the end of
+    /// the line and may appear between any two values.
+    fn skip_space(&mut self) {
+        while let Some(byte) = self.peek() {
+            if byte == b'#' {
+                self.skip_line();
+            } else if byte.is_ascii_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(byte) = self.bump() {
+            if byte == b'\n' {
+                break;
+            }
+        }
+    }
+
+    /// The next run of non-whitespace, which is empty at the end of the
+    /// data.
+    fn token(&mut self) -> &'a [u8] {
+        self.skip_space();
+        if self.peek().is_some() {
+            self.token_line = self.line;
+        }
+        let start = self.pos;
+        while let Some(byte) = self.peek() {
+            if byte.is_ascii_whitespace() || byte == b'#' {
+                break;
+            }
+            self.bump();
+        }
+        &self.data[start..self.pos]
+    }
+
+    fn number(&mut self, what: &str) -> Result<u32, Error> {
+        let token = self.token();
+        if token.is_empty() {
+            return Err(self.error(format!("missing {}", what)));
+        }
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|t| t.parse().ok())
+            .ok_or_else(|| {
+                self.error(format!(
+                    "invalid {} {:?}",
+                    what,
+                    String::from_utf8_lossy(token)
+                ))
+            })
+    }
+
+    fn dimension(&mut self, what: &str) -> Result<usize, Error> {
+        match self.number(what)? {
+            0 => Err(self.error(format!("{} must be at least 1", what))),
+            n => Ok(n as usize),
+        }
+    }
+
+    fn maxval(&mut self) -> Result<u32, Error> {
+        match self.number("maximum value")? {
+            n @ 1..=65535 => Ok(n),
+            n => Err(self.error(format!("maximum value {} is out of range", n))),
+        }
+    }
+
+    fn canvas(mut self) -> Result<Canvas, Error> {
+        let header = self.header()?;
+        let samples = header
+            .width
+            .checked_mul(header.height)
+            .and_then(|n| n.checked_mul(header.depth))
+            .ok_or_else(|| self.error("image is too large"))?;
+
+        // Every sample takes at least a byte, so a header claiming more
+        // than the data could hold is caught before allocating for it.
+        let remaining = self.data.len() - self.pos;
+        let needed = match header.raster {
+            Raster::PackedBits => header.height * header.width.div_ceil(8),
+            Raster::Raw if header.maxval > 255 => samples
+                .checked_mul(2)
+                .ok_or_else(|| self.error("image is too large"))?,
+            _ => samples,
+        };
+        if remaining < needed {
+            return Err(self.truncated(header.raster));
+        }
+
+        let mut canvas = Canvas::new(header.width, header.height);
+        match header.raster {
+            Raster::PackedBits => self.packed_bits(&mut canvas)?,
+            Raster::PlainBits => self.samples(&mut canvas, &header, Parser::plain_bit)?,
+            Raster::Plain => self.samples(&mut canvas, &header, |p| p.number("sample"))?,
+            Raster::Raw if header.maxval > 255 => self.samples(&mut canvas, &header, |p| {
+                let bytes = p.raw_bytes(2)?;
+                Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as u32)
+            })?,
+            Raster::Raw => self.samples(&mut canvas, &header, |p| Ok(p.raw_bytes(1)?[0] as u32))?,
+        }
+        Ok(canvas)
+    }
+
+    fn header(&mut self) -> Result<Header, Error> {
+        let magic = self.token();
+        let (raster, depth) = match magic {
+            b"P1" => (Raster::PlainBits, 1),
+            b"P2" => (Raster::Plain, 1),
+            b"P3" => (Raster::Plain, 3),
+            b"P4" => (Raster::PackedBits, 1),
+            b"P5" => (Raster::Raw, 1),
+            b"P6" => (Raster::Raw, 3),
+            b"P7" => return self.pam_header(),
+            b"" => return Err(self.error("empty image")),
+            _ => {
+                return Err(self.error(format!(
+                    "unsupported image type {:?}",
+                    String::from_utf8_lossy(magic)
+                )))
+            }
+        };
+
+        let width = self.dimension("width")?;
+        let height = self.dimension("height")?;
+        let maxval = match raster {
+            Raster::PlainBits | Raster::PackedBits => 1,
+            _ => self.maxval()?,
+        };
+
+        // Binary data starts after exactly one whitespace character, since
+        // the data itself might begin with bytes that look like spaces.
+        if let Raster::PackedBits | Raster::Raw = raster {
+            match self.bump() {
+                Some(byte) if byte.is_ascii_whitespace() => (),
+                _ => return Err(self.error("expected whitespace before image data")),
+            }
+        }
+
+        Ok(Header {
+            width,
+            height,
+            depth,
+            maxval,
+            raster,
+        })
+    }
+
+    /// PAM headers are a line per field, ending with `ENDHDR`. The tuple
+    /// type is ignored, as the depth is enough to know which channels
+    /// are colour.
+    fn pam_header(&mut self) -> Result<Header, Error> {
+        let (mut width, mut height, mut depth, mut maxval) = (None, None, None, None);
+
+        loop {
+            match self.token() {
+                b"WIDTH" => width = Some(self.dimension("width")?),
+                b"HEIGHT" => height = Some(self.dimension("height")?),
+                b"DEPTH" => depth = Some(self.number("depth")? as usize),
+                b"MAXVAL" => maxval = Some(self.maxval()?),
+                b"TUPLTYPE" => self.skip_line(),
+                b"ENDHDR" => {
+                    self.skip_line();
+                    break;
+                }
+                b"" => return Err(self.error("missing ENDHDR")),
+                other => {
+                    return Err(self.error(format!(
+                        "unknown header field {:?}",
+                        String::from_utf8_lossy(other)
+                    )))
+                }
+            }
+        }
+
+        let missing = |what: &str| self.error(format!("missing {}", what));
+        let depth = depth.ok_or_else(|| missing("DEPTH"))?;
+        if !(1..=4).contains(&depth) {
+            return Err(self.error(format!("unsupported depth {}", depth)));
+        }
+
+        Ok(Header {
+            width: width.ok_or_else(|| missing("WIDTH"))?,
+            height: height.ok_or_else(|| missing("HEIGHT"))?,
+            depth,
+            maxval: maxval.ok_or_else(|| missing("MAXVAL"))?,
+            raster: Raster::Raw,
+        })
+    }
+
+    /// Reads `depth` samples for each pixel in turn. Depths of one and
+    /// two are grey, with or without alpha; three and four are colour.
+    fn samples<F>(&mut self, canvas: &mut Canvas, header: &Header, mut next: F) -> Result<(), Error>
+    where
+        F: FnMut(&mut Self) -> Result<u32, Error>,
+    {
+        let max = header.maxval as f64;
+        let mut samples = [0; 4];
+
+        for pixel in canvas.pixels.iter_mut() {
+            for sample in samples.iter_mut().take(header.depth) {
+                *sample = next(self)?;
+                if *sample > header.maxval {
+                    return Err(self.error(format!(
+                        "sample {} exceeds maximum value {}",
+                        sample, header.maxval
+                    )));
+                }
+            }
+            let channel = |i: usize| samples[i] as f64 / max;
+            *pixel = if header.depth < 3 {
+                Colour::new(channel(0), channel(0), channel(0))
+            } else {
+                Colour::new(channel(0), channel(1), channel(2))
+            };
+        }
+        Ok(())
+    }
+
+    /// A PBM pixel as a brightness, so that it can share the code for
+    /// other images with a maximum value of one.
+    fn plain_bit(&mut self) -> Result<u32, Error> {
+        self.skip_space();
+        self.token_line = self.line;
+        match self.bump() {
+            Some(b'0') => Ok(1),
+            Some(b'1') => Ok(0),
+            Some(byte) => Err(self.error(format!("invalid bit {:?}", byte as char))),
+            None => Err(self.truncated(Raster::PlainBits)),
+        }
+    }
+
+    fn packed_bits(&mut self, canvas: &mut Canvas) -> Result<(), Error> {
+        let width = canvas.width;
+        for row in canvas.pixels.chunks_mut(width) {
+            let bytes = self.raw_bytes(width.div_ceil(8))?;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                let bit = (bytes[x / 8] >> (7 - x % 8)) & 1;
+                *pixel = if bit == 1 {
+                    Colour::black()
+                } else {
+                    Colour::white()
+                };
+            }
+        }
+        Ok(())
+    }
+
+    fn raw_bytes(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + count)
+            .ok_or_else(|| self.truncated(Raster::Raw))?;
+        self.pos += count;
+        Ok(bytes)
+    }
+
+    /// Plain data is reported at its last line, binary data at the end
+    /// of the header.
+    fn truncated(&self, raster: Raster) -> Error {
+        let line = match raster {
+            Raster::PlainBits | Raster::Plain => {
+                let end = self
+                    .data
+                    .iter()
+                    .rposition(|b| !b.is_ascii_whitespace())
+                    .unwrap_or(0);
+                1 + self.data[..end].iter().filter(|&&b| b == b'\n').count()
+            }
+            Raster::PackedBits | Raster::Raw => self.token_line,
+        };
+        Error::parse(line, "image data ends early")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read(data: &[u8]) -> Canvas {
+        Canvas::read_ppm(data).unwrap()
+    }
+
+    fn error_line(data: &[u8]) -> usize {
+        match Canvas::read_ppm(data).unwrap_err() {
+            Error::Parse { line, .. } => line,
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    fn ascii_ppm(c: &Canvas) -> String {
+        let mut out = Vec::new();
+        c.write_ppm(&mut out, PpmFormat::Ascii).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn read_ppm_pixels() {
+        let c = read(
+            b"P3\n4 3\n255\n\
+              255 127 0  0 127 255  127 255 0  255 255 255\n\
+              0 0 0  255 0 0  0 255 0  0 0 255\n\
+              255 255 0  0 255 255  255 0 255  127 127 127\n",
+        );
+
+        assert_eq!((c.width(), c.height()), (4, 3));
+        assert_eq!(c.get_pixel(0, 0), Colour::new(1.0, 127.0 / 255.0, 0.0));
+        assert_eq!(c.get_pixel(3, 1), Colour::new(0.0, 0.0, 1.0));
+        assert_eq!(
+            c.get_pixel(3, 2),
+            Colour::new(127.0 / 255.0, 127.0 / 255.0, 127.0 / 255.0)
+        );
+    }
+
+    #[test]
+    fn read_ppm_with_comments_and_scale() {
+        let c = read(b"P3\n# made by hand\n2 1\n100\n100 100 100 # white\n50 50 50\n");
+
+        assert_eq!(c.get_pixel(0, 0), Colour::white());
+        assert_eq!(c.get_pixel(1, 0), Colour::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn read_ppm_wrong_magic() {
+        assert_eq!(error_line(b"P32\n1 1\n255\n0 0 0\n"), 1);
+    }
+
+    #[test]
+    fn read_ppm_truncated() {
+        assert_eq!(error_line(b"P3\n2 1\n255\n0 0 0\n0 0\n"), 5);
+    }
+
+    #[test]
+    fn read_plain_bitmap() {
+        // Bits needn't be separated, and carriage returns are whitespace.
+        let c = read(b"P1\r\n3 2\r\n010\r\n1 0 1\r\n");
+
+        assert_eq!(c.get_pixel(0, 0), Colour::white());
+        assert_eq!(c.get_pixel(1, 0), Colour::black());
+        assert_eq!(c.get_pixel(0, 1), Colour::black());
+        assert_eq!(c.get_pixel(1, 1), Colour::white());
+    }
+
+    #[test]
+    fn read_packed_bitmap() {
+        // Each ten pixel row is padded out to two bytes.
+        let c = read(b"P4\n10 2\n\x80\x40\x00\xff");
+
+        assert_eq!(c.get_pixel(0, 0), Colour::black());
+        assert_eq!(c.get_pixel(1, 0), Colour::white());
+        assert_eq!(c.get_pixel(9, 0), Colour::black());
+        assert_eq!(c.get_pixel(0, 1), Colour::white());
+        assert_eq!(c.get_pixel(9, 1), Colour::black());
+    }
+
+    #[test]
+    fn read_plain_greymap() {
+        let c = read(b"P2 2 1 4 1 4");
+
+        assert_eq!(c.get_pixel(0, 0), Colour::new(0.25, 0.25, 0.25));
+        assert_eq!(c.get_pixel(1, 0), Colour::white());
+    }
+
+    #[test]
+    fn read_sixteen_bit_greymap() {
+        let c = read(b"P5\n2 1\n65535\n\x80\x00\xff\xff");
+        let half = 32768.0 / 65535.0;
+
+        assert_eq!(c.get_pixel(0, 0), Colour::new(half, half, half));
+        assert_eq!(c.get_pixel(1, 0), Colour::white());
+    }
+
+    #[test]
+    fn read_binary_pixmap_starting_with_whitespace() {
+        // The first red sample is a newline, which must not be skipped.
+        let c = read(b"P6\n1 1\n# comment\n255\n\n\x00\xff");
+
+        assert_eq!(c.get_pixel(0, 0), Colour::new(10.0 / 255.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn read_pam() {
+        let c = read(
+            b"P7\nWIDTH 2\nHEIGHT 1\nDEPTH 4\nMAXVAL 255\n\
+              # with alpha\nTUPLTYPE RGB_ALPHA\nENDHDR\n\
+              \xff\x00\x00\x80\x00\xff\x00\xff",
+        );
+
+        assert_eq!(c.get_pixel(0, 0), Colour::new(1.0, 0.0, 0.0));
+        assert_eq!(c.get_pixel(1, 0), Colour::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn read_grey_pam() {
+        let c = read(b"P7\nWIDTH 1\nHEIGHT 1\nDEPTH 2\nMAXVAL 1\nENDHDR\n\x01\x00");
+
+        assert_eq!(c.get_pixel(0, 0), Colour::white());
+    }
+
+    #[test]
+    fn pam_missing_field() {
+        let err = Canvas::read_ppm(&b"P7\nWIDTH 1\nDEPTH 3\nMAXVAL 255\nENDHDR\n\0\0\0"[..]);
+
+        assert!(err.unwrap_err().to_string().contains("HEIGHT"));
+    }
+
+    #[test]
+    fn sample_over_maximum() {
+        assert_eq!(error_line(b"P2\n1 1\n15\n16\n"), 4);
+    }
+
+    #[test]
+    fn zero_width() {
+        assert_eq!(error_line(b"P3\n0 1\n255\n"), 2);
+    }
+
+    #[test]
+    fn out_of_range_maximum() {
+        assert_eq!(error_line(b"P5 1 1 65536\n\0\0"), 1);
+    }
+
+    #[test]
+    fn huge_header_on_short_file() {
+        assert_eq!(error_line(b"P5 4294967295 4294967295 65535\n\0\0"), 1);
+        assert_eq!(error_line(b"P6 4294967296 4294967296 255\n\0\0"), 1);
+    }
+
+    #[test]
+    fn truncated_binary_data() {
+        assert_eq!(error_line(b"P6\n2 2\n255\n\0\0\0\0\0\0"), 3);
+    }
+
+    #[test]
+    fn ppm_header() {
+        let ppm = ascii_ppm(&Canvas::new(5, 3));
+
+        assert_eq!(
+            ppm.lines().take(3).collect::<Vec<_>>(),
+            ["P3", "5 3", "255"]
+        );
+    }
+
+    #[test]
+    fn ppm_pixel_data() {
+        let mut c = Canvas::new(5, 3);
+        c.write_pixel(0, 0, Colour::new(1.5, 0.0, 0.0));
+        c.write_pixel(2, 1, Colour::new(0.0, 0.5, 0.0));
+        c.write_pixel(4, 2, Colour::new(-0.5, 0.0, 1.0));
+        let ppm = ascii_ppm(&c);
+
+        assert_eq!(
+            ppm.lines().skip(3).collect::<Vec<_>>(),
+            [
+                "255 0 0 0 0 0 0 0 0 0 0 0 0 0 0",
+                "0 0 0 0 0 0 0 127 0 0 0 0 0 0 0",
+                "0 0 0 0 0 0 0 0 0 0 0 0 0 0 255",
+            ]
+        );
+    }
+
+    #[test]
+    fn ppm_splits_long_lines() {
+        let mut c = Canvas::new(10, 2);
+        for y in 0..2 {
+            for x in 0..10 {
+                c.write_pixel(x, y, Colour::new(1.0, 0.8, 0.6));
+            }
+        }
+        let ppm = ascii_ppm(&c);
+
+        assert_eq!(
+            ppm.lines().skip(3).collect::<Vec<_>>(),
+            [
+                "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+                "153 255 204 153 255 204 153 255 204 153 255 204 153",
+                "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+                "153 255 204 153 255 204 153 255 204 153 255 204 153",
+            ]
+        );
+        assert!(ppm.lines().all(|line| line.len() <= 70));
+    }
+
+    #[test]
+    fn ppm_ends_with_newline() {
+        assert!(ascii_ppm(&Canvas::new(5, 3)).ends_with('\n'));
+    }
+
+    #[test]
+    fn binary_ppm() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Colour::new(1.0, 0.5, 0.0));
+        c.write_pixel(1, 0, Colour::new(0.0, 0.0, 1.0));
+        let mut out = Vec::new();
+        c.write_ppm(&mut out, PpmFormat::Binary).unwrap();
+
+        assert_eq!(out, b"P6\n2 1\n255\n\xff\x7f\x00\x00\x00\xff");
+    }
+
+    #[test]
+    fn ppm_round_trips() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(1, 1, Colour::new(1.0, 0.2, 0.4));
+
+        for format in [PpmFormat::Ascii, PpmFormat::Binary] {
+            let mut out = Vec::new();
+            c.write_ppm(&mut out, format).unwrap();
+            assert_eq!(read(&out), c);
+        }
+    }
+}
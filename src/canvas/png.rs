@@ -0,0 +1,653 @@
+//! PNG images. The chunk structure and filtering are handled here; only
+//! the zlib compression of the pixel data is left to `miniz_oxide`.
+
+use super::Canvas;
+use crate::colour::Colour;
+use crate::error::Error;
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// The most compressed data written to a single IDAT chunk.
+const IDAT_SIZE: usize = 1 << 20;
+
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// The largest width or height a PNG may have.
+const MAX_SIZE: usize = (1 << 31) - 1;
+
+/// Where each of the seven Adam7 interlacing passes starts, as x and y,
+/// and how far apart its pixels are.
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// The layouts which can be written. A canvas has no transparency, so
+/// the alpha formats are always fully opaque; they're for tools which
+/// insist on an alpha channel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PngFormat {
+    Rgb8,
+    Rgba8,
+    Rgb16,
+    Rgba16,
+}
+
+impl PngFormat {
+    fn bit_depth(self) -> u8 {
+        match self {
+            PngFormat::Rgb8 | PngFormat::Rgba8 => 8,
+            PngFormat::Rgb16 | PngFormat::Rgba16 => 16,
+        }
+    }
+
+    fn colour_type(self) -> u8 {
+        match self {
+            PngFormat::Rgb8 | PngFormat::Rgb16 => 2,
+            PngFormat::Rgba8 | PngFormat::Rgba16 => 6,
+        }
+    }
+}
+
+impl Canvas {
+    pub fn open_png<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Canvas::read_png(file)
+    }
+
+    /// Reads any standard PNG: grey, palette or colour, at any bit depth,
+    /// interlaced or not. Alpha is dropped, and so are ancillary chunks
+    /// such as gamma.
+    pub fn read_png<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        decode(&data)
+    }
+
+    /// Writes the image to a file, creating or truncating it.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P, format: PngFormat) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        self.write_png(std::io::BufWriter::new(file), format)
+    }
+
+    /// Channels are rounded to the nearest level rather than truncated
+    /// as for PPM, so that images read back in are written out unchanged
+    /// at either depth.
+    pub fn write_png<W: Write>(&self, mut w: W, format: PngFormat) -> Result<(), Error> {
+        let width = u32::try_from(self.width).map_err(|_| Error::image("too wide for PNG"))?;
+        let height = u32::try_from(self.height()).map_err(|_| Error::image("too tall for PNG"))?;
+        let depth = format.bit_depth();
+        let max = ((1_u32 << depth) - 1) as f64;
+        let alpha = format.colour_type() == 6;
+        let channels = if alpha { 4 } else { 3 };
+        let bpp = channels * depth as usize / 8;
+
+        let mut raw = Vec::with_capacity((self.width * bpp + 1) * self.height());
+        let mut row = Vec::with_capacity(self.width * bpp);
+        let mut prev = vec![0; self.width * bpp];
+        for pixels in self.pixels.chunks(self.width) {
+            row.clear();
+            for pixel in pixels {
+                for channel in [pixel.red(), pixel.green(), pixel.blue()] {
+                    let level = (channel.clamp(0.0, 1.0) * max).round() as u16;
+                    push_sample(&mut row, level, depth);
+                }
+                if alpha {
+                    push_sample(&mut row, max as u16, depth);
+                }
+            }
+            filter_row(&row, &prev, bpp, &mut raw);
+            std::mem::swap(&mut row, &mut prev);
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        // Compression, filtering and interlacing are all the defaults.
+        header.extend([depth, format.colour_type(), 0, 0, 0]);
+
+        w.write_all(&SIGNATURE)?;
+        write_chunk(&mut w, b"IHDR", &header)?;
+        for part in compress_to_vec_zlib(&raw, COMPRESSION_LEVEL).chunks(IDAT_SIZE) {
+            write_chunk(&mut w, b"IDAT", part)?;
+        }
+        write_chunk(&mut w, b"IEND", &[])?;
+        w.flush()?;
+        Ok(())
+    }
+}
+
+fn push_sample(row: &mut Vec<u8>, level: u16, depth: u8) {
+    if depth == 16 {
+        row.extend(level.to_be_bytes());
+    } else {
+        row.push(level as u8);
+    }
+}
+
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> Result<(), Error> {
+    let length = u32::try_from(data.len()).map_err(|_| Error::image("chunk too long"))?;
+    w.write_all(&length.to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    w.write_all(&crc32(&[kind, data]).to_be_bytes())?;
+    Ok(())
+}
+
+/// The CRC used by PNG and zlib, run over several slices in turn.
+fn crc32(parts: &[&[u8]]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut n = 0;
+        while n < 256 {
+            let mut c = n as u32;
+            let mut k = 0;
+            while k < 8 {
+                c = if c & 1 == 1 {
+                    0xedb8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+                k += 1;
+            }
+            table[n] = c;
+            n += 1;
+        }
+        table
+    };
+
+    let mut crc = 0xffff_ffff_u32;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        crc = TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc ^ 0xffff_ffff
+}
+
+/// Each row is stored as the difference from a guess at each byte based
+/// on its neighbours: nothing, the byte a pixel to the left, the byte
+/// above, their average, or the Paeth predictor.
+fn predict(filter: u8, left: u8, up: u8, up_left: u8) -> u8 {
+    match filter {
+        0 => 0,
+        1 => left,
+        2 => up,
+        3 => ((left as u16 + up as u16) / 2) as u8,
+        _ => paeth(left, up, up_left),
+    }
+}
+
+/// Whichever neighbour is closest to left + up - up_left.
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let p = left as i16 + up as i16 - up_left as i16;
+    let (pa, pb, pc) = (
+        (p - left as i16).abs(),
+        (p - up as i16).abs(),
+        (p - up_left as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        left
+    } else if pb <= pc {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// The left, up and up-left neighbours of byte `i`, which are zero
+/// outside the image.
+fn neighbours(row: &[u8], prev: &[u8], i: usize, bpp: usize) -> (u8, u8, u8) {
+    if i >= bpp {
+        (row[i - bpp], prev[i], prev[i - bpp])
+    } else {
+        (0, prev[i], 0)
+    }
+}
+
+fn filtered<'a>(
+    filter: u8,
+    row: &'a [u8],
+    prev: &'a [u8],
+    bpp: usize,
+) -> impl Iterator<Item = u8> + 'a {
+    (0..row.len()).map(move |i| {
+        let (left, up, up_left) = neighbours(row, prev, i, bpp);
+        row[i].wrapping_sub(predict(filter, left, up, up_left))
+    })
+}
+
+/// Appends the row with whichever filter leaves the smallest
+/// differences, which is the usual guess at what will compress best.
+fn filter_row(row: &[u8], prev: &[u8], bpp: usize, out: &mut Vec<u8>) {
+    let cost = |filter| -> u64 {
+        filtered(filter, row, prev, bpp)
+            .map(|b| (b as i8).unsigned_abs() as u64)
+            .sum()
+    };
+    let best = (0..5).min_by_key(|&filter| cost(filter)).unwrap_or(0);
+    out.push(best);
+    out.extend(filtered(best, row, prev, bpp));
+}
+
+fn unfilter(filter: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> Result<(), Error> {
+    if filter > 4 {
+        return Err(Error::image(format!("unknown filter type {}", filter)));
+    }
+    for i in 0..row.len() {
+        let (left, up, up_left) = neighbours(row, prev, i, bpp);
+        row[i] = row[i].wrapping_add(predict(filter, left, up, up_left));
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    colour_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != 13 {
+            return Err(Error::image("IHDR chunk is the wrong size"));
+        }
+        let be = |i: usize| u32::from_be_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        let header = Header {
+            width: be(0) as usize,
+            height: be(4) as usize,
+            depth: data[8],
+            colour_type: data[9],
+            interlaced: data[12] == 1,
+        };
+
+        if header.width == 0 || header.height == 0 {
+            return Err(Error::image("image has no pixels"));
+        }
+        if header.width > MAX_SIZE || header.height > MAX_SIZE {
+            return Err(Error::image("image is too large"));
+        }
+        let depths: &[u8] = match header.colour_type {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            other => return Err(Error::image(format!("unknown colour type {}", other))),
+        };
+        if !depths.contains(&header.depth) {
+            return Err(Error::image(format!(
+                "bit depth {} is invalid for colour type {}",
+                header.depth, header.colour_type
+            )));
+        }
+        if data[10] != 0 || data[11] != 0 || data[12] > 1 {
+            return Err(Error::image(
+                "unknown compression, filter or interlace method",
+            ));
+        }
+        Ok(header)
+    }
+
+    fn channels(&self) -> usize {
+        match self.colour_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.depth as usize
+    }
+
+    /// None if the row is too long to be addressed.
+    fn row_bytes(&self, width: usize) -> Option<usize> {
+        Some(width.checked_mul(self.bits_per_pixel())?.div_ceil(8))
+    }
+
+    /// Each pass's first pixel and spacing, and its size in pixels. A
+    /// plain image is a single pass.
+    fn passes(&self) -> Vec<(usize, usize, usize, usize, usize, usize)> {
+        let size = |length: usize, start: usize, step: usize| {
+            (length + step - 1).saturating_sub(start) / step
+        };
+        let passes: &[_] = if self.interlaced {
+            &ADAM7
+        } else {
+            &[(0, 0, 1, 1)]
+        };
+        passes
+            .iter()
+            .map(|&(x, y, dx, dy)| {
+                (
+                    x,
+                    y,
+                    dx,
+                    dy,
+                    size(self.width, x, dx),
+                    size(self.height, y, dy),
+                )
+            })
+            .filter(|&(.., w, h)| w > 0 && h > 0)
+            .collect()
+    }
+}
+
+/// Splits off the next chunk, checking its CRC.
+fn next_chunk<'a>(data: &mut &'a [u8]) -> Result<(&'a [u8], &'a [u8]), Error> {
+    let truncated = || Error::image("file ends early");
+    let length = data.get(..4).ok_or_else(truncated)?;
+    let length = u32::from_be_bytes([length[0], length[1], length[2], length[3]]) as usize;
+    let kind = data.get(4..8).ok_or_else(truncated)?;
+    let body = data.get(8..8 + length).ok_or_else(truncated)?;
+    let crc = data.get(8 + length..12 + length).ok_or_else(truncated)?;
+
+    if crc32(&[kind, body]).to_be_bytes() != crc {
+        return Err(Error::image(format!(
+            "{} chunk is corrupt",
+            String::from_utf8_lossy(kind)
+        )));
+    }
+    *data = &data[12 + length..];
+    Ok((kind, body))
+}
+
+fn decode(data: &[u8]) -> Result<Canvas, Error> {
+    let mut data = data
+        .strip_prefix(&SIGNATURE[..])
+        .ok_or_else(|| Error::image("not a PNG file"))?;
+
+    let header = match next_chunk(&mut data)? {
+        (b"IHDR", body) => Header::parse(body)?,
+        _ => return Err(Error::image("missing IHDR chunk")),
+    };
+    let mut palette: Option<&[u8]> = None;
+    let mut compressed = Vec::new();
+    loop {
+        match next_chunk(&mut data)? {
+            (b"IEND", _) => break,
+            (b"IDAT", body) => compressed.extend_from_slice(body),
+            (b"PLTE", body) if body.len() % 3 == 0 => palette = Some(body),
+            (b"PLTE", _) => return Err(Error::image("PLTE chunk is the wrong size")),
+            // Chunks with a lowercase first letter are safe to ignore.
+            (kind, _) if kind[0].is_ascii_uppercase() => {
+                return Err(Error::image(format!(
+                    "unsupported {} chunk",
+                    String::from_utf8_lossy(kind)
+                )))
+            }
+            _ => (),
+        }
+    }
+    if header.colour_type == 3 && palette.is_none() {
+        return Err(Error::image("missing PLTE chunk"));
+    }
+
+    let too_large = || Error::image("image is too large");
+    let passes = header.passes();
+    let expected = passes
+        .iter()
+        .try_fold(0_usize, |total, &(.., w, h)| {
+            let row = header.row_bytes(w)?.checked_add(1)?;
+            total.checked_add(row.checked_mul(h)?)
+        })
+        .ok_or_else(too_large)?;
+    let mut raw = decompress_to_vec_zlib_with_limit(&compressed, expected)
+        .map_err(|e| Error::image(format!("corrupt image data: {}", e)))?;
+    if raw.len() < expected {
+        return Err(Error::image("image data ends early"));
+    }
+
+    let bpp = header.bits_per_pixel().div_ceil(8);
+    let mut canvas = Canvas::new(header.width, header.height);
+    let mut rows = &mut raw[..];
+    for (x0, y0, dx, dy, width, height) in passes {
+        let stride = header.row_bytes(width).ok_or_else(too_large)?;
+        let mut prev = vec![0; stride];
+        for y in 0..height {
+            let (line, rest) = rows.split_at_mut(stride + 1);
+            rows = rest;
+            let (filter, row) = line.split_at_mut(1);
+            unfilter(filter[0], row, &prev, bpp)?;
+            for x in 0..width {
+                let colour = pixel_colour(&header, palette, row, x)?;
+                canvas.write_pixel(x0 + x * dx, y0 + y * dy, colour);
+            }
+            prev.copy_from_slice(row);
+        }
+    }
+    Ok(canvas)
+}
+
+/// Sample `index` of an unfiltered row. Depths under eight are packed
+/// with the leftmost sample in the high bits.
+fn sample(row: &[u8], index: usize, depth: u8) -> u16 {
+    match depth {
+        16 => u16::from_be_bytes([row[2 * index], row[2 * index + 1]]),
+        8 => row[index] as u16,
+        _ => {
+            let bit = index * depth as usize;
+            let shift = 8 - depth as usize - bit % 8;
+            ((row[bit / 8] >> shift) & ((1 << depth) - 1)) as u16
+        }
+    }
+}
+
+fn pixel_colour(
+    header: &Header,
+    palette: Option<&[u8]>,
+    row: &[u8],
+    x: usize,
+) -> Result<Colour, Error> {
+    let first = x * header.channels();
+    let channel = |i: usize| {
+        sample(row, first + i, header.depth) as f64 / ((1_u32 << header.depth) - 1) as f64
+    };
+
+    Ok(match header.colour_type {
+        0 | 4 => Colour::new(channel(0), channel(0), channel(0)),
+        2 | 6 => Colour::new(channel(0), channel(1), channel(2)),
+        _ => {
+            let index = sample(row, first, header.depth) as usize;
+            let entry = palette
+                .and_then(|p| p.get(index * 3..index * 3 + 3))
+                .ok_or_else(|| Error::image(format!("palette has no entry {}", index)))?;
+            let level = |i: usize| entry[i] as f64 / 255.0;
+            Colour::new(level(0), level(1), level(2))
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A PNG with the given header fields around some already filtered
+    /// rows, for covering what the writer never produces.
+    fn png(width: u32, height: u32, fields: [u8; 5], palette: &[u8], raw: &[u8]) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        header.extend(fields);
+
+        let mut out = SIGNATURE.to_vec();
+        write_chunk(&mut out, b"IHDR", &header).unwrap();
+        if !palette.is_empty() {
+            write_chunk(&mut out, b"PLTE", palette).unwrap();
+        }
+        write_chunk(&mut out, b"tEXt", b"Comment\0ignored").unwrap();
+        write_chunk(&mut out, b"IDAT", &compress_to_vec_zlib(raw, 6)).unwrap();
+        write_chunk(&mut out, b"IEND", &[]).unwrap();
+        out
+    }
+
+    fn gradient() -> Canvas {
+        let mut c = Canvas::new(7, 5);
+        for y in 0..5 {
+            for x in 0..7 {
+                let colour = Colour::new(x as f64 / 6.0, y as f64 / 4.0, (x * y) as f64 / 24.0);
+                c.write_pixel(x, y, colour);
+            }
+        }
+        c
+    }
+
+    fn encode(c: &Canvas, format: PngFormat) -> Vec<u8> {
+        let mut out = Vec::new();
+        c.write_png(&mut out, format).unwrap();
+        out
+    }
+
+    #[test]
+    fn crc_of_empty_chunk() {
+        assert_eq!(crc32(&[b"IEND", &[]]), 0xae42_6082);
+    }
+
+    #[test]
+    fn header() {
+        let out = encode(&Canvas::new(3, 2), PngFormat::Rgba16);
+
+        assert_eq!(out[..8], SIGNATURE);
+        assert_eq!(&out[12..16], b"IHDR");
+        assert_eq!(out[16..29], [0, 0, 0, 3, 0, 0, 0, 2, 16, 6, 0, 0, 0]);
+        assert!(out.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    }
+
+    #[test]
+    fn round_trips() {
+        // 8 bit channels only hold multiples of 1/255, so the gradient is
+        // first snapped to those.
+        let eight_bit = Canvas::read_png(&encode(&gradient(), PngFormat::Rgb8)[..]).unwrap();
+
+        for format in [PngFormat::Rgb8, PngFormat::Rgba8] {
+            let out = encode(&eight_bit, format);
+            assert_eq!(Canvas::read_png(&out[..]).unwrap(), eight_bit);
+        }
+        // At 16 bits the levels are finer than colours compare at, so
+        // they're only expected within half a level.
+        for format in [PngFormat::Rgb16, PngFormat::Rgba16] {
+            let out = encode(&gradient(), format);
+            let read = Canvas::read_png(&out[..]).unwrap();
+            for (a, b) in read.pixels.iter().zip(gradient().pixels.iter()) {
+                let error = (a.red() - b.red())
+                    .abs()
+                    .max((a.green() - b.green()).abs())
+                    .max((a.blue() - b.blue()).abs());
+                assert!(error <= 0.5 / 65535.0, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn filters_invert() {
+        let prev = [3, 200, 17, 255, 0, 90];
+        let row = [250, 1, 128, 64, 255, 7];
+
+        for filter in 0..5 {
+            let mut line: Vec<u8> = filtered(filter, &row, &prev, 2).collect();
+            unfilter(filter, &mut line, &prev, 2).unwrap();
+            assert_eq!(line, row, "filter {}", filter);
+        }
+    }
+
+    #[test]
+    fn read_palette() {
+        // Three two-bit indices, padded out to a byte.
+        let palette = [255, 0, 0, 0, 255, 0, 0, 0, 255];
+        let c = Canvas::read_png(&png(3, 1, [2, 3, 0, 0, 0], &palette, &[0, 0b00_01_10_00])[..])
+            .unwrap();
+
+        assert_eq!(c.get_pixel(0, 0), Colour::new(1.0, 0.0, 0.0));
+        assert_eq!(c.get_pixel(1, 0), Colour::new(0.0, 1.0, 0.0));
+        assert_eq!(c.get_pixel(2, 0), Colour::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn read_grey_with_alpha() {
+        // The second row is stored relative to the first.
+        let raw = [0, 51, 255, 2, 51, 0];
+        let c = Canvas::read_png(&png(1, 2, [8, 4, 0, 0, 0], &[], &raw)[..]).unwrap();
+
+        assert_eq!(c.get_pixel(0, 0), Colour::new(0.2, 0.2, 0.2));
+        assert_eq!(c.get_pixel(0, 1), Colour::new(0.4, 0.4, 0.4));
+    }
+
+    #[test]
+    fn read_interlaced() {
+        // A 3x3 image is covered by five of the seven passes, in this
+        // order.
+        let order = [
+            (0, 0),
+            (2, 0),
+            (0, 2),
+            (2, 2),
+            (1, 0),
+            (1, 2),
+            (0, 1),
+            (1, 1),
+            (2, 1),
+        ];
+        let level = |(x, y): (usize, usize)| (y * 3 + x) as u8 * 20;
+        let mut raw = Vec::new();
+        for pass in [
+            &order[0..1],
+            &order[1..2],
+            &order[2..4],
+            &order[4..5],
+            &order[5..6],
+            &order[6..9],
+        ] {
+            raw.push(0);
+            raw.extend(pass.iter().map(|&p| level(p)));
+        }
+        let c = Canvas::read_png(&png(3, 3, [8, 0, 0, 0, 1], &[], &raw)[..]).unwrap();
+
+        for &(x, y) in order.iter() {
+            let grey = level((x, y)) as f64 / 255.0;
+            assert_eq!(c.get_pixel(x, y), Colour::new(grey, grey, grey));
+        }
+    }
+
+    #[test]
+    fn corrupt_chunk() {
+        let mut out = encode(&Canvas::new(2, 2), PngFormat::Rgb8);
+        out[20] ^= 1;
+
+        assert!(matches!(Canvas::read_png(&out[..]), Err(Error::Image(_))));
+    }
+
+    #[test]
+    fn not_a_png() {
+        assert!(matches!(
+            Canvas::read_png(&b"P3\n1 1\n255\n0 0 0\n"[..]),
+            Err(Error::Image(_))
+        ));
+    }
+
+    #[test]
+    fn huge_header_on_short_file() {
+        for size in [u32::MAX, (1 << 31) - 1] {
+            let out = png(size, size, [16, 6, 0, 0, 0], &[], &[0; 16]);
+
+            assert!(matches!(Canvas::read_png(&out[..]), Err(Error::Image(_))));
+        }
+    }
+
+    #[test]
+    fn truncated_image_data() {
+        let out = png(2, 2, [8, 2, 0, 0, 0], &[], &[0; 7]);
+
+        assert!(matches!(Canvas::read_png(&out[..]), Err(Error::Image(_))));
+    }
+}
@@ -1,12 +1,27 @@
 use crate::TAU;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone)]
 pub struct Colour {
     red: f64,
     green: f64,
     blue: f64,
 }
 
+impl PartialEq for Colour {
+    fn eq(&self, other: &Colour) -> bool {
+        let lr = (self.red * 100000.0).round();
+        let rr = (other.red * 100000.0).round();
+        let lg = (self.green * 100000.0).round();
+        let rg = (other.green * 100000.0).round();
+        let lb = (self.blue * 100000.0).round();
+        let rb = (other.blue * 100000.0).round();
+        if lr != rr || lg != rg || lb != rb {
+            return false;
+        }
+        true
+    }
+}
+
 impl Colour {
     pub fn new(red: f64, green: f64, blue: f64) -> Self {
         Colour { red, green, blue }
@@ -20,6 +35,18 @@ impl Colour {
         Colour::new(1.0, 1.0, 1.0)
     }
 
+    pub fn red(&self) -> f64 {
+        self.red
+    }
+
+    pub fn green(&self) -> f64 {
+        self.green
+    }
+
+    pub fn blue(&self) -> f64 {
+        self.blue
+    }
+
     pub fn red_u8(&self) -> u8 {
         (clamp(self.red, 0.0, 1.0) * 255.0).trunc() as u8
     }
@@ -57,14 +84,14 @@ fn clamp(test: f64, min: f64, max: f64) -> f64 {
 #[inline]
 fn arr_min(arr: &[f64]) -> f64 {
     let init = arr[0];
-    arr.into_iter()
+    arr.iter()
         .fold(init, |res, val| if res < *val { res } else { *val })
 }
 
 #[inline]
 fn arr_max(arr: &[f64]) -> f64 {
     let init = arr[0];
-    arr.into_iter()
+    arr.iter()
         .fold(init, |res, val| if res > *val { res } else { *val })
 }
 
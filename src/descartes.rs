@@ -132,6 +132,10 @@ impl Vector3D<f64> {
 
         Vector3D::new(x, y, z)
     }
+
+    pub fn reflect(self, normal: Self) -> Self {
+        self - normal * 2.0 * self.dot(normal)
+    }
 }
 
 impl<T: std::ops::Sub<Output = T>> std::ops::Sub<Point3D<T>> for Point3D<T> {
@@ -243,4 +247,20 @@ mod tests {
         assert_eq!(v1.flt().cross(v2), Vector3D::new(-1.0, 2.0, -1.0));
         assert_eq!(v2.cross(v1.flt()), Vector3D::new(1.0, -2.0, 1.0));
     }
+
+    #[test]
+    fn reflect_at_45_degrees() {
+        let v = Vector3D::new(1.0, -1.0, 0.0);
+        let n = Vector3D::new(0.0, 1.0, 0.0);
+
+        assert_eq!(v.reflect(n), Vector3D::new(1.0, 1.0, 0.0))
+    }
+
+    #[test]
+    fn reflect_off_slanted_surface() {
+        let v = Vector3D::new(0.0, -1.0, 0.0);
+        let n = Vector3D::new(2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0, 0.0);
+
+        assert_eq!(v.reflect(n), Vector3D::new(1.0, 0.0, 0.0))
+    }
 }
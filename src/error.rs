@@ -1,8 +1,26 @@
-pub(crate) use anyhow::{Context, Result};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("invalid image: {0}")]
+    Image(String),
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
+
+impl Error {
+    pub(crate) fn parse(line: usize, message: impl Into<String>) -> Self {
+        Error::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn image(message: impl Into<String>) -> Self {
+        Error::Image(message.into())
+    }
+}
@@ -1,9 +1,20 @@
+pub mod bounds;
+pub mod bvh;
+pub mod camera;
 pub mod canvas;
 pub mod colour;
 pub mod descartes;
 pub mod error;
+pub mod lighting;
 pub mod matrix;
+pub mod obj;
+pub mod patterns;
 pub mod raycasting;
+pub mod shapes;
 pub mod transformations;
+pub mod world;
 
 pub const TAU: f64 = 2.0 * std::f64::consts::PI;
+/// Tolerance used to nudge points off surfaces so that rays spawned
+/// from them don't immediately hit the surface again.
+pub const EPSILON: f64 = 0.00001;
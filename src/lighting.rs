@@ -0,0 +1,355 @@
+use crate::colour::Colour;
+use crate::descartes::{Point3D, Vector3D};
+use crate::patterns::Pattern;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointLight {
+    position: Point3D<f64>,
+    intensity: Colour,
+}
+
+impl PointLight {
+    pub fn new(position: Point3D<f64>, intensity: Colour) -> Self {
+        PointLight {
+            position,
+            intensity,
+        }
+    }
+
+    pub fn position(&self) -> Point3D<f64> {
+        self.position
+    }
+
+    pub fn intensity(&self) -> Colour {
+        self.intensity
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Material {
+    colour: Colour,
+    pattern: Option<Rc<dyn Pattern>>,
+    ambient: f64,
+    diffuse: f64,
+    specular: f64,
+    shininess: f64,
+    reflective: f64,
+    transparency: f64,
+    refractive_index: f64,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material::new(Colour::white(), 0.1, 0.9, 0.9, 200.0)
+    }
+}
+
+impl Material {
+    pub fn new(colour: Colour, ambient: f64, diffuse: f64, specular: f64, shininess: f64) -> Self {
+        Material {
+            colour,
+            pattern: None,
+            ambient,
+            diffuse,
+            specular,
+            shininess,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+        }
+    }
+
+    pub fn colour(&self) -> Colour {
+        self.colour
+    }
+
+    pub fn set_colour(&mut self, colour: Colour) {
+        self.colour = colour
+    }
+
+    pub fn pattern(&self) -> Option<&dyn Pattern> {
+        self.pattern.as_deref()
+    }
+
+    /// Paints the material with a pattern, which takes the place of
+    /// its flat colour. The pattern is shared between clones of the
+    /// material.
+    pub fn set_pattern<P: Pattern + 'static>(&mut self, pattern: P) {
+        self.pattern = Some(Rc::new(pattern))
+    }
+
+    /// Goes back to the flat colour.
+    pub fn clear_pattern(&mut self) {
+        self.pattern = None
+    }
+
+    /// The colour at a point in the space of the object the material
+    /// is on.
+    pub fn colour_at(&self, object_point: Point3D<f64>) -> Colour {
+        match &self.pattern {
+            Some(pattern) => pattern.colour_at(object_point),
+            None => self.colour,
+        }
+    }
+
+    pub fn ambient(&self) -> f64 {
+        self.ambient
+    }
+
+    pub fn set_ambient(&mut self, ambient: f64) {
+        self.ambient = ambient
+    }
+
+    pub fn diffuse(&self) -> f64 {
+        self.diffuse
+    }
+
+    pub fn set_diffuse(&mut self, diffuse: f64) {
+        self.diffuse = diffuse
+    }
+
+    pub fn specular(&self) -> f64 {
+        self.specular
+    }
+
+    pub fn set_specular(&mut self, specular: f64) {
+        self.specular = specular
+    }
+
+    pub fn shininess(&self) -> f64 {
+        self.shininess
+    }
+
+    pub fn set_shininess(&mut self, shininess: f64) {
+        self.shininess = shininess
+    }
+
+    /// How much of the surrounding scene the surface mirrors, from 0
+    /// for matte to 1 for a perfect mirror.
+    pub fn reflective(&self) -> f64 {
+        self.reflective
+    }
+
+    pub fn set_reflective(&mut self, reflective: f64) {
+        self.reflective = reflective
+    }
+
+    /// How much light passes through the surface, from 0 for opaque to
+    /// 1 for perfectly clear.
+    pub fn transparency(&self) -> f64 {
+        self.transparency
+    }
+
+    pub fn set_transparency(&mut self, transparency: f64) {
+        self.transparency = transparency
+    }
+
+    /// How much light bends on entering the material: 1.0 for a
+    /// vacuum, about 1.33 for water and 1.5 for glass.
+    pub fn refractive_index(&self) -> f64 {
+        self.refractive_index
+    }
+
+    pub fn set_refractive_index(&mut self, refractive_index: f64) {
+        self.refractive_index = refractive_index
+    }
+}
+
+/// Patterns can't be compared, so two materials are only equal if they
+/// share the very same pattern.
+impl PartialEq for Material {
+    fn eq(&self, other: &Self) -> bool {
+        let same_pattern = match (&self.pattern, &other.pattern) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+
+        same_pattern
+            && self.colour == other.colour
+            && self.ambient == other.ambient
+            && self.diffuse == other.diffuse
+            && self.specular == other.specular
+            && self.shininess == other.shininess
+            && self.reflective == other.reflective
+            && self.transparency == other.transparency
+            && self.refractive_index == other.refractive_index
+    }
+}
+
+/// Shades a single point using the Phong reflection model: the sum of
+/// ambient, diffuse and specular contributions from one light. A point
+/// in shadow only receives the ambient term.
+///
+/// `object_point` is the same point in the space of the object being
+/// shaded, where the material's pattern is looked up.
+pub fn lighting(
+    material: &Material,
+    light: &PointLight,
+    point: Point3D<f64>,
+    object_point: Point3D<f64>,
+    eyev: Vector3D<f64>,
+    normalv: Vector3D<f64>,
+    in_shadow: bool,
+) -> Colour {
+    let effective_colour = material.colour_at(object_point) * light.intensity();
+    let lightv = (light.position() - point).normalize();
+    let ambient = effective_colour * material.ambient();
+
+    if in_shadow {
+        return ambient;
+    }
+
+    // A negative cosine means the light is on the other side of the
+    // surface, so it contributes nothing beyond the ambient term.
+    let light_dot_normal = lightv.dot(normalv);
+    if light_dot_normal < 0.0 {
+        return ambient;
+    }
+
+    let diffuse = effective_colour * material.diffuse() * light_dot_normal;
+
+    // Likewise, a negative cosine here means the reflection points
+    // away from the eye.
+    let reflectv = (-lightv).reflect(normalv);
+    let reflect_dot_eye = reflectv.dot(eyev);
+    let specular = if reflect_dot_eye <= 0.0 {
+        Colour::black()
+    } else {
+        let factor = reflect_dot_eye.powf(material.shininess());
+        light.intensity() * material.specular() * factor
+    };
+
+    ambient + diffuse + specular
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup() -> (Material, Point3D<f64>) {
+        (Material::default(), Point3D::new(0.0, 0.0, 0.0))
+    }
+
+    #[test]
+    fn default_material() {
+        let m = Material::default();
+
+        assert_eq!(m.colour(), Colour::white());
+        assert_eq!(m.ambient(), 0.1);
+        assert_eq!(m.diffuse(), 0.9);
+        assert_eq!(m.specular(), 0.9);
+        assert_eq!(m.shininess(), 200.0);
+    }
+
+    #[test]
+    fn eye_between_light_and_surface() {
+        let (m, position) = setup();
+        let eyev = Vector3D::new(0.0, 0.0, -1.0);
+        let normalv = Vector3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point3D::new(0.0, 0.0, -10.0), Colour::white());
+
+        assert_eq!(
+            lighting(&m, &light, position, position, eyev, normalv, false),
+            Colour::new(1.9, 1.9, 1.9)
+        )
+    }
+
+    #[test]
+    fn eye_offset_45_degrees() {
+        let (m, position) = setup();
+        let eyev = Vector3D::new(0.0, 2.0_f64.sqrt() / 2.0, -(2.0_f64.sqrt() / 2.0));
+        let normalv = Vector3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point3D::new(0.0, 0.0, -10.0), Colour::white());
+
+        assert_eq!(
+            lighting(&m, &light, position, position, eyev, normalv, false),
+            Colour::new(1.0, 1.0, 1.0)
+        )
+    }
+
+    #[test]
+    fn light_offset_45_degrees() {
+        let (m, position) = setup();
+        let eyev = Vector3D::new(0.0, 0.0, -1.0);
+        let normalv = Vector3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point3D::new(0.0, 10.0, -10.0), Colour::white());
+
+        assert_eq!(
+            lighting(&m, &light, position, position, eyev, normalv, false),
+            Colour::new(0.7364, 0.7364, 0.7364)
+        )
+    }
+
+    #[test]
+    fn eye_in_reflection_path() {
+        let (m, position) = setup();
+        let eyev = Vector3D::new(0.0, -(2.0_f64.sqrt() / 2.0), -(2.0_f64.sqrt() / 2.0));
+        let normalv = Vector3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point3D::new(0.0, 10.0, -10.0), Colour::white());
+
+        assert_eq!(
+            lighting(&m, &light, position, position, eyev, normalv, false),
+            Colour::new(1.6364, 1.6364, 1.6364)
+        )
+    }
+
+    #[test]
+    fn surface_in_shadow() {
+        let (m, position) = setup();
+        let eyev = Vector3D::new(0.0, 0.0, -1.0);
+        let normalv = Vector3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point3D::new(0.0, 0.0, -10.0), Colour::white());
+
+        assert_eq!(
+            lighting(&m, &light, position, position, eyev, normalv, true),
+            Colour::new(0.1, 0.1, 0.1)
+        )
+    }
+
+    #[test]
+    fn light_behind_surface() {
+        let (m, position) = setup();
+        let eyev = Vector3D::new(0.0, 0.0, -1.0);
+        let normalv = Vector3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point3D::new(0.0, 0.0, 10.0), Colour::white());
+
+        assert_eq!(
+            lighting(&m, &light, position, position, eyev, normalv, false),
+            Colour::new(0.1, 0.1, 0.1)
+        )
+    }
+
+    #[test]
+    fn lighting_with_pattern() {
+        use crate::patterns::Stripe;
+
+        let mut m = Material::new(Colour::black(), 1.0, 0.0, 0.0, 200.0);
+        m.set_pattern(Stripe::new(Colour::white(), Colour::black()));
+        let eyev = Vector3D::new(0.0, 0.0, -1.0);
+        let normalv = Vector3D::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point3D::new(0.0, 0.0, -10.0), Colour::white());
+        let shade = |x| {
+            let point = Point3D::new(x, 0.0, 0.0);
+            lighting(&m, &light, point, point, eyev, normalv, false)
+        };
+
+        assert_eq!(shade(0.9), Colour::white());
+        assert_eq!(shade(1.1), Colour::black());
+    }
+
+    #[test]
+    fn materials_sharing_pattern() {
+        use crate::patterns::Stripe;
+
+        let mut m = Material::default();
+        m.set_pattern(Stripe::new(Colour::white(), Colour::black()));
+        let copy = m.clone();
+        let mut other = Material::default();
+        other.set_pattern(Stripe::new(Colour::white(), Colour::black()));
+
+        assert_eq!(m, copy);
+        assert_ne!(m, other);
+    }
+}
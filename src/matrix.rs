@@ -18,6 +18,12 @@ impl PartialEq for M2 {
     }
 }
 
+impl Default for M2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl M2 {
     pub fn new() -> Self {
         M2([[0.0; 2]; 2])
@@ -68,6 +74,12 @@ impl PartialEq for M3 {
     }
 }
 
+impl Default for M3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl M3 {
     pub fn new() -> Self {
         M3([[0.0; 3]; 3])
@@ -85,31 +97,21 @@ impl M3 {
         let mut result = M2::new();
 
         for row in 0..3 {
-            let nrow;
             // Skip the row we're eliminating
             if row == r {
                 continue;
             }
             // Rows greater than the removed one have their index
             // lowered by one.
-            if row > r {
-                nrow = row - 1
-            } else {
-                nrow = row
-            }
+            let nrow = if row > r { row - 1 } else { row };
             for col in 0..3 {
-                let ncol;
                 // Skip the column we're eliminating
                 if col == c {
                     continue;
                 }
                 // Columns greater than the removed one have their
                 // index lowered by one.
-                if col > c {
-                    ncol = col - 1
-                } else {
-                    ncol = col
-                }
+                let ncol = if col > c { col - 1 } else { col };
                 result.write_idx(nrow, ncol, self.idx(row, col))
             }
         }
@@ -126,7 +128,7 @@ impl M3 {
 
     pub fn cofactor(&self, r: usize, c: usize) -> f64 {
         let m = self.minor(r, c);
-        if (r + c) % 2 == 0 {
+        if (r + c).is_multiple_of(2) {
             return m;
         }
         -m
@@ -170,6 +172,12 @@ impl PartialEq for M4 {
     }
 }
 
+impl Default for M4 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl M4 {
     pub fn new() -> Self {
         M4([[0.0; 4]; 4])
@@ -187,31 +195,21 @@ impl M4 {
         let mut result = M3::new();
 
         for row in 0..4 {
-            let nrow;
             // Skip the row we're eliminating
             if row == r {
                 continue;
             }
             // Rows greater than the removed one have their index
             // lowered by one.
-            if row > r {
-                nrow = row - 1
-            } else {
-                nrow = row
-            }
+            let nrow = if row > r { row - 1 } else { row };
             for col in 0..4 {
-                let ncol;
                 // Skip the column we're eliminating
                 if col == c {
                     continue;
                 }
                 // Columns greater than the removed one have their
                 // index lowered by one.
-                if col > c {
-                    ncol = col - 1
-                } else {
-                    ncol = col
-                }
+                let ncol = if col > c { col - 1 } else { col };
                 result.write_idx(nrow, ncol, self.idx(row, col))
             }
         }
@@ -224,7 +222,7 @@ impl M4 {
 
     pub fn cofactor(&self, r: usize, c: usize) -> f64 {
         let m = self.minor(r, c);
-        if (r + c) % 2 == 0 {
+        if (r + c).is_multiple_of(2) {
             return m;
         }
         -m
@@ -444,7 +442,7 @@ mod test {
     #[test]
     fn transpose_identity() {
         let identity = M4::identity();
-        let transposed = identity.clone().transpose();
+        let transposed = identity.transpose();
 
         assert_eq!(identity, transposed);
     }
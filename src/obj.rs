@@ -0,0 +1,481 @@
+//! Loading of Wavefront OBJ meshes. Only geometry is read: vertices,
+//! normals, texture coordinates, faces and named groups. Everything
+//! else is skipped and its line number recorded.
+
+use crate::descartes::{Point3D, Vector3D};
+use crate::error::Error;
+use crate::raycasting::Scene;
+use crate::shapes::{Shape, SmoothTriangle, Triangle};
+use crate::world::World;
+use std::io::BufRead;
+use std::path::Path;
+
+/// A named set of faces, already broken down into triangles.
+#[derive(Debug)]
+pub struct ObjGroup {
+    name: String,
+    triangles: Vec<Box<dyn Shape>>,
+}
+
+impl ObjGroup {
+    fn new(name: &str) -> Self {
+        ObjGroup {
+            name: name.to_string(),
+            triangles: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn triangles(&self) -> &[Box<dyn Shape>] {
+        &self.triangles
+    }
+}
+
+#[derive(Debug)]
+pub struct ObjFile {
+    vertices: Vec<Point3D<f64>>,
+    normals: Vec<Vector3D<f64>>,
+    texture_coords: Vec<(f64, f64)>,
+    groups: Vec<ObjGroup>,
+    ignored: Vec<usize>,
+}
+
+/// One corner of a face, as indices into the file's vertex, texture
+/// coordinate and normal lists.
+struct FaceVertex {
+    vertex: usize,
+    normal: Option<usize>,
+}
+
+impl ObjFile {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        ObjFile::from_reader(std::io::BufReader::new(file))
+    }
+
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        ObjFile::from_reader(input.as_bytes())
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, Error> {
+        let mut obj = ObjFile {
+            vertices: Vec::new(),
+            normals: Vec::new(),
+            texture_coords: Vec::new(),
+            // Faces before the first `g` statement belong to an
+            // unnamed default group.
+            groups: vec![ObjGroup::new("")],
+            ignored: Vec::new(),
+        };
+
+        for (idx, line) in reader.lines().enumerate() {
+            obj.parse_line(idx + 1, &line?)?;
+        }
+
+        Ok(obj)
+    }
+
+    fn parse_line(&mut self, line_no: usize, line: &str) -> Result<(), Error> {
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(k) if !k.starts_with('#') => k,
+            // Blank lines and comments aren't worth reporting.
+            _ => return Ok(()),
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats::<3>(line_no, &args)?;
+                self.vertices.push(Point3D::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = parse_floats::<3>(line_no, &args)?;
+                self.normals.push(Vector3D::new(x, y, z));
+            }
+            "vt" => {
+                // The v coordinate is optional and defaults to zero.
+                let u = parse_floats::<1>(line_no, &args)?[0];
+                let v = match args.get(1) {
+                    Some(v) => parse_float(line_no, v)?,
+                    None => 0.0,
+                };
+                self.texture_coords.push((u, v));
+            }
+            "f" => self.parse_face(line_no, &args)?,
+            "g" => {
+                let name = args.join(" ");
+                self.groups.push(ObjGroup::new(&name));
+            }
+            _ => self.ignored.push(line_no),
+        }
+
+        Ok(())
+    }
+
+    fn parse_face(&mut self, line_no: usize, args: &[&str]) -> Result<(), Error> {
+        if args.len() < 3 {
+            return Err(Error::parse(line_no, "a face needs at least 3 vertices"));
+        }
+
+        let corners = args
+            .iter()
+            .map(|arg| self.parse_face_vertex(line_no, arg))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Polygons are split into a fan of triangles around the first
+        // vertex, which is correct for any convex polygon.
+        let group = self.groups.last_mut().unwrap();
+        for i in 1..corners.len() - 1 {
+            let (a, b, c) = (&corners[0], &corners[i], &corners[i + 1]);
+            let (p1, p2, p3) = (
+                self.vertices[a.vertex],
+                self.vertices[b.vertex],
+                self.vertices[c.vertex],
+            );
+
+            let triangle: Box<dyn Shape> = match (a.normal, b.normal, c.normal) {
+                (Some(n1), Some(n2), Some(n3)) => Box::new(SmoothTriangle::new(
+                    p1,
+                    p2,
+                    p3,
+                    self.normals[n1],
+                    self.normals[n2],
+                    self.normals[n3],
+                )),
+                _ => Box::new(Triangle::new(p1, p2, p3)),
+            };
+            group.triangles.push(triangle);
+        }
+
+        Ok(())
+    }
+
+    /// Reads one of `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+    fn parse_face_vertex(&self, line_no: usize, arg: &str) -> Result<FaceVertex, Error> {
+        let mut parts = arg.split('/');
+        let vertex = resolve_index(line_no, parts.next(), self.vertices.len(), "vertex")?;
+
+        if let Some(vt) = parts.next().filter(|s| !s.is_empty()) {
+            resolve_index(line_no, Some(vt), self.texture_coords.len(), "texture")?;
+        }
+
+        let normal = match parts.next().filter(|s| !s.is_empty()) {
+            Some(vn) => Some(resolve_index(
+                line_no,
+                Some(vn),
+                self.normals.len(),
+                "normal",
+            )?),
+            None => None,
+        };
+
+        Ok(FaceVertex { vertex, normal })
+    }
+
+    pub fn vertices(&self) -> &[Point3D<f64>] {
+        &self.vertices
+    }
+
+    pub fn normals(&self) -> &[Vector3D<f64>] {
+        &self.normals
+    }
+
+    pub fn texture_coords(&self) -> &[(f64, f64)] {
+        &self.texture_coords
+    }
+
+    pub fn default_group(&self) -> &ObjGroup {
+        &self.groups[0]
+    }
+
+    /// Named groups, in the order they appear in the file.
+    pub fn groups(&self) -> &[ObjGroup] {
+        &self.groups[1..]
+    }
+
+    pub fn group(&self, name: &str) -> Option<&ObjGroup> {
+        self.groups().iter().find(|g| g.name == name)
+    }
+
+    /// Line numbers of statements which were not understood and so
+    /// were skipped.
+    pub fn ignored(&self) -> &[usize] {
+        &self.ignored
+    }
+
+    /// Moves every triangle into `scene`, returning their ids.
+    pub fn into_scene(self, scene: &Scene) -> Vec<usize> {
+        self.groups
+            .into_iter()
+            .flat_map(|g| g.triangles)
+            .map(|t| scene.add_boxed(t).id())
+            .collect()
+    }
+
+    /// Moves every triangle into `world` under a single new group,
+    /// with each named group in the file becoming a group within it.
+    /// Returns the id of the outer group, which can be placed with
+    /// `World::set_transform`.
+    pub fn into_group(self, world: &mut World) -> usize {
+        let root = world.group().id();
+        for (idx, group) in self.groups.into_iter().enumerate() {
+            // Faces from the default group go straight into the root.
+            let parent = if idx == 0 {
+                root
+            } else {
+                let id = world.group().id();
+                world.add_child(root, id);
+                id
+            };
+
+            for triangle in group.triangles {
+                let id = world.add_boxed(triangle).id();
+                world.add_child(parent, id);
+            }
+        }
+        root
+    }
+}
+
+fn parse_float(line_no: usize, arg: &str) -> Result<f64, Error> {
+    arg.parse()
+        .map_err(|_| Error::parse(line_no, format!("expected a number, found {:?}", arg)))
+}
+
+/// Parses the first `N` arguments as floats; any beyond that, like the
+/// optional `w` of a vertex, are not needed.
+fn parse_floats<const N: usize>(line_no: usize, args: &[&str]) -> Result<[f64; N], Error> {
+    if args.len() < N {
+        return Err(Error::parse(
+            line_no,
+            format!("expected {} numbers, found {}", N, args.len()),
+        ));
+    }
+
+    let mut result = [0.0; N];
+    for (slot, arg) in result.iter_mut().zip(args) {
+        *slot = parse_float(line_no, arg)?;
+    }
+    Ok(result)
+}
+
+/// Converts a 1-based OBJ index, which may be negative to count back
+/// from the most recent entry, into an index into a list of `len`.
+fn resolve_index(
+    line_no: usize,
+    arg: Option<&str>,
+    len: usize,
+    kind: &str,
+) -> Result<usize, Error> {
+    let arg = arg.unwrap_or("");
+    let idx: isize = arg
+        .parse()
+        .map_err(|_| Error::parse(line_no, format!("invalid {} index {:?}", kind, arg)))?;
+
+    let resolved = if idx < 0 { len as isize + idx } else { idx - 1 };
+
+    if resolved < 0 || resolved as usize >= len {
+        return Err(Error::parse(
+            line_no,
+            format!("{} index {} is out of range", kind, idx),
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::raycasting::Ray;
+    use crate::transformations::Transformation;
+
+    #[test]
+    fn ignore_unrecognised_lines() {
+        let gibberish = "There was a young lady named Bright\n\
+                         who traveled much faster than light.\n\
+                         She set out one day\n\
+                         in a relative way,\n\
+                         and came back the previous night.\n";
+        let obj = ObjFile::parse(gibberish).unwrap();
+
+        assert_eq!(obj.ignored(), &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn comments_and_blanks_are_not_reported() {
+        let obj = ObjFile::parse("# a comment\n\n   \nv 1 2 3\n").unwrap();
+
+        assert!(obj.ignored().is_empty());
+        assert_eq!(obj.vertices().len(), 1);
+    }
+
+    #[test]
+    fn vertex_records() {
+        let obj = ObjFile::parse("v -1 1 0\nv -1.0000 0.5000 0.0000\nv 1 0 0\nv 1 1 0\n").unwrap();
+
+        assert_eq!(
+            obj.vertices(),
+            &[
+                Point3D::new(-1.0, 1.0, 0.0),
+                Point3D::new(-1.0, 0.5, 0.0),
+                Point3D::new(1.0, 0.0, 0.0),
+                Point3D::new(1.0, 1.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn triangle_faces() {
+        let obj =
+            ObjFile::parse("v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\nf 1 2 3\nf 1 3 4\n").unwrap();
+        let triangles = obj.default_group().triangles();
+
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(triangles[0].bounds().min(), Point3D::new(-1.0, 0.0, 0.0));
+        assert_eq!(triangles[1].bounds().max(), Point3D::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn triangulate_polygons() {
+        let obj = ObjFile::parse("v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\nv 0 2 0\n\nf 1 2 3 4 5\n")
+            .unwrap();
+
+        assert_eq!(obj.default_group().triangles().len(), 3);
+    }
+
+    #[test]
+    fn named_groups() {
+        let obj = ObjFile::parse(
+            "v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\n\
+             g FirstGroup\nf 1 2 3\ng SecondGroup\nf 1 3 4\n",
+        )
+        .unwrap();
+
+        assert!(obj.default_group().triangles().is_empty());
+        assert_eq!(obj.groups().len(), 2);
+        assert_eq!(obj.group("FirstGroup").unwrap().triangles().len(), 1);
+        assert_eq!(obj.group("SecondGroup").unwrap().triangles().len(), 1);
+    }
+
+    #[test]
+    fn vertex_normals_and_texture_coords() {
+        let obj =
+            ObjFile::parse("vn 0 0 1\nvn 0.707 0 -0.707\nvn 1 2 3\nvt 0.5 0.25\nvt 0.1\n").unwrap();
+
+        assert_eq!(
+            obj.normals(),
+            &[
+                Vector3D::new(0.0, 0.0, 1.0),
+                Vector3D::new(0.707, 0.0, -0.707),
+                Vector3D::new(1.0, 2.0, 3.0),
+            ]
+        );
+        assert_eq!(obj.texture_coords(), &[(0.5, 0.25), (0.1, 0.0)]);
+    }
+
+    #[test]
+    fn faces_with_normals() {
+        let obj = ObjFile::parse(
+            "v 0 1 0\nv -1 0 0\nv 1 0 0\n\
+             vn -1 0 0\nvn 1 0 0\nvn 0 1 0\nvt 0 0\n\
+             f 1//3 2//1 3//2\nf 1/1/3 2/1/1 3/1/2\n",
+        )
+        .unwrap();
+        let triangles = obj.default_group().triangles();
+        let hit = crate::raycasting::Intersection::with_uv(0.0, 0, 0.0, 0.0);
+
+        assert_eq!(triangles.len(), 2);
+        for t in triangles {
+            // With u and v both zero, the normal is that of p1.
+            assert_eq!(
+                t.local_normal_at(Point3D::new(0.0, 1.0, 0.0), &hit),
+                Vector3D::new(0.0, 1.0, 0.0)
+            );
+        }
+    }
+
+    #[test]
+    fn negative_indices() {
+        let obj = ObjFile::parse("v 0 1 0\nv -1 0 0\nv 1 0 0\nf -3 -2 -1\n").unwrap();
+
+        assert_eq!(obj.default_group().triangles().len(), 1);
+    }
+
+    #[test]
+    fn malformed_vertex() {
+        let err = ObjFile::parse("v 1 2 3\nv 1 two 3\n").unwrap_err();
+
+        match err {
+            Error::Parse { line, .. } => assert_eq!(line, 2),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn face_index_out_of_range() {
+        let err = ObjFile::parse("v 0 1 0\nv -1 0 0\nf 1 2 3\n").unwrap_err();
+
+        match err {
+            Error::Parse { line, .. } => assert_eq!(line, 3),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn triangles_into_scene() {
+        let obj =
+            ObjFile::parse("v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\ng A\nf 1 2 3\ng B\nf 1 3 4\n")
+                .unwrap();
+        let scene = Scene::new();
+        scene.sphere();
+
+        assert_eq!(obj.into_scene(&scene), vec![1, 2]);
+    }
+
+    #[test]
+    fn triangles_into_group() {
+        let obj = ObjFile::parse(
+            "v -1 1 0\nv -1 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 4\ng A\nf 1 2 3\ng B\nf 1 3 4\n",
+        )
+        .unwrap();
+        let mut w = World::new();
+        w.sphere();
+        let root = obj.into_group(&mut w);
+
+        assert_eq!(root, 1);
+        assert_eq!(w.object(root).children(), &[2, 3, 5]);
+        assert_eq!(w.object(3).children(), &[4]);
+        assert_eq!(w.object(5).children(), &[6]);
+    }
+
+    #[test]
+    fn transformed_group_intersects() {
+        let obj = ObjFile::parse("v -1 1 0\nv -1 -1 0\nv 1 -1 0\ng A\nf 1 2 3\n").unwrap();
+        let mut w = World::new();
+        let root = obj.into_group(&mut w);
+        let r = Ray::new(
+            Point3D::new(-0.5, -0.25, -5.0),
+            Vector3D::new(0.0, 0.0, 1.0),
+        );
+        assert_eq!(w.intersect(&r).len(), 1);
+
+        w.set_transform(
+            root,
+            Transformation::translation(0.0, 0.0, 2.0) * Transformation::scaling(-1.0, 1.0, 1.0),
+        );
+
+        assert!(w.intersect(&r).is_empty());
+        let r = Ray::new(Point3D::new(0.5, -0.25, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = w.intersect(&r);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t(), 7.0);
+        assert_eq!(
+            w.normal_at(xs[0].object(), r.position(7.0), &xs[0]),
+            Vector3D::new(0.0, 0.0, -1.0)
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use super::{Pattern, Placement};
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+
+/// The average of two patterns at every point, such as two stripes at
+/// right angles to weave a plaid.
+#[derive(Debug)]
+pub struct Blend {
+    placement: Placement,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
+}
+
+impl Blend {
+    pub fn new<A: Pattern + 'static, B: Pattern + 'static>(a: A, b: B) -> Self {
+        Blend {
+            placement: Placement::default(),
+            a: Box::new(a),
+            b: Box::new(b),
+        }
+    }
+
+    pub fn a(&self) -> &dyn Pattern {
+        &*self.a
+    }
+
+    pub fn b(&self) -> &dyn Pattern {
+        &*self.b
+    }
+}
+
+impl Pattern for Blend {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour {
+        (self.a.colour_at(point) + self.b.colour_at(point)) * 0.5
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::patterns::Stripe;
+    use crate::transformations::Transformation;
+
+    #[test]
+    fn averages_patterns() {
+        let mut across = Stripe::new(Colour::white(), Colour::black());
+        across.set_transform(Transformation::rotation_y(std::f64::consts::PI / 2.0));
+        let p = Blend::new(Stripe::new(Colour::white(), Colour::black()), across);
+
+        assert_eq!(
+            p.local_colour_at(Point3D::new(0.5, 0.0, 0.5)),
+            Colour::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(
+            p.local_colour_at(Point3D::new(0.5, 0.0, -0.5)),
+            Colour::white()
+        );
+        assert_eq!(
+            p.local_colour_at(Point3D::new(1.5, 0.0, 0.5)),
+            Colour::black()
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use super::{Pattern, Placement, Solid};
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+
+/// Alternating unit cubes of two colours, like a three dimensional
+/// chess board.
+#[derive(Debug)]
+pub struct Checker {
+    placement: Placement,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
+}
+
+impl Checker {
+    pub fn new(a: Colour, b: Colour) -> Self {
+        Checker::nested(Solid::new(a), Solid::new(b))
+    }
+
+    /// Uses patterns in place of each colour. They are looked up with
+    /// points in this pattern's space, then apply their own transforms.
+    pub fn nested<A: Pattern + 'static, B: Pattern + 'static>(a: A, b: B) -> Self {
+        Checker {
+            placement: Placement::default(),
+            a: Box::new(a),
+            b: Box::new(b),
+        }
+    }
+
+    pub fn a(&self) -> &dyn Pattern {
+        &*self.a
+    }
+
+    pub fn b(&self) -> &dyn Pattern {
+        &*self.b
+    }
+}
+
+impl Pattern for Checker {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour {
+        let sum = point.x().floor() + point.y().floor() + point.z().floor();
+        if (sum as i64).rem_euclid(2) == 0 {
+            self.a.colour_at(point)
+        } else {
+            self.b.colour_at(point)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn repeats_in_each_axis() {
+        let p = Checker::new(Colour::white(), Colour::black());
+        let cases = [
+            (Point3D::new(0.0, 0.0, 0.0), Colour::white()),
+            (Point3D::new(0.99, 0.0, 0.0), Colour::white()),
+            (Point3D::new(1.01, 0.0, 0.0), Colour::black()),
+            (Point3D::new(0.0, 0.99, 0.0), Colour::white()),
+            (Point3D::new(0.0, 1.01, 0.0), Colour::black()),
+            (Point3D::new(0.0, 0.0, 0.99), Colour::white()),
+            (Point3D::new(0.0, 0.0, 1.01), Colour::black()),
+        ];
+
+        for (point, colour) in cases.iter() {
+            assert_eq!(p.local_colour_at(*point), *colour);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use super::uv::UvPattern;
+use super::{Pattern, Placement};
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+
+/// The faces of an axis-aligned cube.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CubeFace {
+    Left,
+    Front,
+    Right,
+    Back,
+    Up,
+    Down,
+}
+
+impl CubeFace {
+    /// The face a point on the unit cube lies on, decided by whichever
+    /// coordinate is largest.
+    pub fn of(point: Point3D<f64>) -> Self {
+        let (x, y, z) = (point.x(), point.y(), point.z());
+        let coord = x.abs().max(y.abs()).max(z.abs());
+
+        if coord == x {
+            CubeFace::Right
+        } else if coord == -x {
+            CubeFace::Left
+        } else if coord == y {
+            CubeFace::Up
+        } else if coord == -y {
+            CubeFace::Down
+        } else if coord == z {
+            CubeFace::Front
+        } else {
+            CubeFace::Back
+        }
+    }
+
+    /// The u/v of a point on this face of the unit cube, oriented as if
+    /// the cube were unfolded flat with the front face facing you.
+    pub fn uv(self, point: Point3D<f64>) -> (f64, f64) {
+        let (x, y, z) = (point.x(), point.y(), point.z());
+        let wrap = |n: f64| n.rem_euclid(2.0) / 2.0;
+
+        match self {
+            CubeFace::Front => (wrap(x + 1.0), wrap(y + 1.0)),
+            CubeFace::Back => (wrap(1.0 - x), wrap(y + 1.0)),
+            CubeFace::Left => (wrap(z + 1.0), wrap(y + 1.0)),
+            CubeFace::Right => (wrap(1.0 - z), wrap(y + 1.0)),
+            CubeFace::Up => (wrap(x + 1.0), wrap(1.0 - z)),
+            CubeFace::Down => (wrap(x + 1.0), wrap(z + 1.0)),
+        }
+    }
+}
+
+/// A separate `UvPattern` on each face of a cube, as for a skybox.
+#[derive(Debug)]
+pub struct CubeMap {
+    placement: Placement,
+    faces: [Box<dyn UvPattern>; 6],
+}
+
+impl CubeMap {
+    /// Faces are given in the order of `CubeFace`: left, front, right,
+    /// back, up, down.
+    pub fn new(faces: [Box<dyn UvPattern>; 6]) -> Self {
+        CubeMap {
+            placement: Placement::default(),
+            faces,
+        }
+    }
+
+    pub fn face(&self, face: CubeFace) -> &dyn UvPattern {
+        &*self.faces[face as usize]
+    }
+}
+
+impl Pattern for CubeMap {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour {
+        let face = CubeFace::of(point);
+        let (u, v) = face.uv(point);
+        self.face(face).uv_colour_at(u, v)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Colours a face's corners and centre differently, to show which
+    /// way up it is.
+    #[derive(Debug)]
+    struct AlignCheck {
+        main: Colour,
+        ul: Colour,
+        ur: Colour,
+        bl: Colour,
+        br: Colour,
+    }
+
+    impl UvPattern for AlignCheck {
+        fn uv_colour_at(&self, u: f64, v: f64) -> Colour {
+            match (u < 0.2, u > 0.8, v < 0.2, v > 0.8) {
+                (true, _, _, true) => self.ul,
+                (_, true, _, true) => self.ur,
+                (true, _, true, _) => self.bl,
+                (_, true, true, _) => self.br,
+                _ => self.main,
+            }
+        }
+    }
+
+    #[test]
+    fn face_of_point() {
+        let cases = [
+            ((-1.0, 0.5, -0.25), CubeFace::Left),
+            ((1.1, -0.75, 0.8), CubeFace::Right),
+            ((0.1, 0.6, 0.9), CubeFace::Front),
+            ((-0.7, 0.0, -2.0), CubeFace::Back),
+            ((0.5, 1.0, 0.9), CubeFace::Up),
+            ((-0.2, -1.3, 1.1), CubeFace::Down),
+        ];
+
+        for ((x, y, z), face) in cases.iter() {
+            assert_eq!(CubeFace::of(Point3D::new(*x, *y, *z)), *face);
+        }
+    }
+
+    #[test]
+    fn face_uvs() {
+        let cases = [
+            (CubeFace::Front, (-0.5, 0.5, 1.0), (0.25, 0.75)),
+            (CubeFace::Front, (0.5, -0.5, 1.0), (0.75, 0.25)),
+            (CubeFace::Back, (0.5, 0.5, -1.0), (0.25, 0.75)),
+            (CubeFace::Back, (-0.5, -0.5, -1.0), (0.75, 0.25)),
+            (CubeFace::Left, (-1.0, 0.5, -0.5), (0.25, 0.75)),
+            (CubeFace::Left, (-1.0, -0.5, 0.5), (0.75, 0.25)),
+            (CubeFace::Right, (1.0, 0.5, 0.5), (0.25, 0.75)),
+            (CubeFace::Right, (1.0, -0.5, -0.5), (0.75, 0.25)),
+            (CubeFace::Up, (-0.5, 1.0, -0.5), (0.25, 0.75)),
+            (CubeFace::Up, (0.5, 1.0, 0.5), (0.75, 0.25)),
+            (CubeFace::Down, (-0.5, -1.0, 0.5), (0.25, 0.75)),
+            (CubeFace::Down, (0.5, -1.0, -0.5), (0.75, 0.25)),
+        ];
+
+        for (face, (x, y, z), uv) in cases.iter() {
+            assert_eq!(face.uv(Point3D::new(*x, *y, *z)), *uv, "{:?}", face);
+        }
+    }
+
+    #[test]
+    fn cube_map_faces() {
+        let red = Colour::new(1.0, 0.0, 0.0);
+        let yellow = Colour::new(1.0, 1.0, 0.0);
+        let brown = Colour::new(1.0, 0.5, 0.0);
+        let green = Colour::new(0.0, 1.0, 0.0);
+        let cyan = Colour::new(0.0, 1.0, 1.0);
+        let blue = Colour::new(0.0, 0.0, 1.0);
+        let purple = Colour::new(1.0, 0.0, 1.0);
+        let white = Colour::white();
+        let face = |main, ul, ur, bl, br| -> Box<dyn UvPattern> {
+            Box::new(AlignCheck {
+                main,
+                ul,
+                ur,
+                bl,
+                br,
+            })
+        };
+        let p = CubeMap::new([
+            face(yellow, cyan, red, blue, brown),
+            face(cyan, red, yellow, brown, green),
+            face(red, yellow, purple, green, white),
+            face(green, purple, cyan, white, blue),
+            face(brown, cyan, purple, red, yellow),
+            face(purple, brown, green, blue, white),
+        ]);
+        let cases = [
+            ((-1.0, 0.0, 0.0), yellow),
+            ((-1.0, 0.9, -0.9), cyan),
+            ((-1.0, 0.9, 0.9), red),
+            ((-1.0, -0.9, -0.9), blue),
+            ((-1.0, -0.9, 0.9), brown),
+            ((0.0, 0.0, 1.0), cyan),
+            ((-0.9, 0.9, 1.0), red),
+            ((0.9, -0.9, 1.0), green),
+            ((1.0, 0.0, 0.0), red),
+            ((1.0, 0.9, 0.9), yellow),
+            ((1.0, -0.9, -0.9), white),
+            ((0.0, 0.0, -1.0), green),
+            ((0.9, 0.9, -1.0), purple),
+            ((-0.9, -0.9, -1.0), blue),
+            ((0.0, 1.0, 0.0), brown),
+            ((-0.9, 1.0, -0.9), cyan),
+            ((0.9, 1.0, 0.9), yellow),
+            ((0.0, -1.0, 0.0), purple),
+            ((-0.9, -1.0, 0.9), brown),
+            ((0.9, -1.0, -0.9), white),
+        ];
+
+        for ((x, y, z), colour) in cases.iter() {
+            assert_eq!(
+                p.colour_at(Point3D::new(*x, *y, *z)),
+                *colour,
+                "{} {} {}",
+                x,
+                y,
+                z
+            );
+        }
+    }
+}
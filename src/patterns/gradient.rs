@@ -0,0 +1,82 @@
+use super::{Pattern, Placement, Solid};
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+
+/// A linear blend from one colour to another along the x axis,
+/// repeating every unit.
+#[derive(Debug)]
+pub struct Gradient {
+    placement: Placement,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
+}
+
+impl Gradient {
+    pub fn new(a: Colour, b: Colour) -> Self {
+        Gradient::nested(Solid::new(a), Solid::new(b))
+    }
+
+    /// Uses patterns in place of each colour. They are looked up with
+    /// points in this pattern's space, then apply their own transforms.
+    pub fn nested<A: Pattern + 'static, B: Pattern + 'static>(a: A, b: B) -> Self {
+        Gradient {
+            placement: Placement::default(),
+            a: Box::new(a),
+            b: Box::new(b),
+        }
+    }
+
+    pub fn a(&self) -> &dyn Pattern {
+        &*self.a
+    }
+
+    pub fn b(&self) -> &dyn Pattern {
+        &*self.b
+    }
+}
+
+impl Pattern for Gradient {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour {
+        let fraction = point.x() - point.x().floor();
+        let (a, b) = (self.a.colour_at(point), self.b.colour_at(point));
+        a + (b - a) * fraction
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn interpolates_between_colours() {
+        let p = Gradient::new(Colour::white(), Colour::black());
+        let cases = [
+            (0.0, Colour::white()),
+            (0.25, Colour::new(0.75, 0.75, 0.75)),
+            (0.5, Colour::new(0.5, 0.5, 0.5)),
+            (0.75, Colour::new(0.25, 0.25, 0.25)),
+        ];
+
+        for (x, colour) in cases.iter() {
+            assert_eq!(p.local_colour_at(Point3D::new(*x, 0.0, 0.0)), *colour);
+        }
+    }
+
+    #[test]
+    fn repeats_every_unit() {
+        let p = Gradient::new(Colour::white(), Colour::black());
+
+        assert_eq!(
+            p.local_colour_at(Point3D::new(1.25, 0.0, 0.0)),
+            Colour::new(0.75, 0.75, 0.75)
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+use crate::matrix::M4;
+use crate::transformations::Transformation;
+
+mod blend;
+mod checker;
+mod cube_map;
+mod gradient;
+mod perlin;
+mod perturb;
+mod ring;
+mod solid;
+mod stripe;
+mod uv;
+
+pub use blend::Blend;
+pub use checker::Checker;
+pub use cube_map::{CubeFace, CubeMap};
+pub use gradient::Gradient;
+pub use perlin::noise;
+pub use perturb::Perturb;
+pub use ring::Ring;
+pub use solid::Solid;
+pub use stripe::Stripe;
+pub use uv::{TextureMap, UvChecker, UvImage, UvMapping, UvPattern};
+
+/// Where a pattern sits relative to the object it is painted on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    transform: Transformation,
+    inverse: M4,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Placement {
+            transform: Transformation::identity(),
+            inverse: M4::identity(),
+        }
+    }
+}
+
+impl Placement {
+    pub fn transform(&self) -> &Transformation {
+        &self.transform
+    }
+
+    pub fn inverse(&self) -> M4 {
+        self.inverse
+    }
+}
+
+/// A colour which varies across the surface of an object.
+///
+/// Like shapes, implementors only work in their own pattern space; the
+/// provided methods move points there from the object's space, so a
+/// pattern can be scaled or rotated independently of its object.
+pub trait Pattern: std::fmt::Debug {
+    fn placement(&self) -> &Placement;
+
+    fn placement_mut(&mut self) -> &mut Placement;
+
+    /// The colour at a point already transformed into pattern space.
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour;
+
+    fn transform(&self) -> &Transformation {
+        &self.placement().transform
+    }
+
+    /// Panics if the transformation cannot be inverted.
+    fn set_transform(&mut self, transform: Transformation) {
+        let placement = self.placement_mut();
+        placement.inverse = transform
+            .matrix()
+            .inverse()
+            .expect("pattern transformations must be invertible");
+        placement.transform = transform;
+    }
+
+    /// The colour at a point in the space of the object the pattern is
+    /// painted on.
+    fn colour_at(&self, object_point: Point3D<f64>) -> Colour {
+        self.local_colour_at(self.placement().inverse * object_point)
+    }
+}
+
+/// A pattern which paints each point with its own coordinates, for
+/// checking which space points arrive in.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct TestPattern {
+    placement: Placement,
+}
+
+#[cfg(test)]
+impl Pattern for TestPattern {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour {
+        Colour::new(point.x(), point.y(), point.z())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_transform() {
+        let p = TestPattern::default();
+
+        assert_eq!(p.transform(), &Transformation::identity());
+    }
+
+    #[test]
+    fn assign_transform() {
+        let mut p = TestPattern::default();
+        p.set_transform(Transformation::translation(1.0, 2.0, 3.0));
+
+        assert_eq!(p.transform(), &Transformation::translation(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn pattern_transformation() {
+        let mut p = TestPattern::default();
+        p.set_transform(Transformation::scaling(2.0, 2.0, 2.0));
+
+        assert_eq!(
+            p.colour_at(Point3D::new(2.0, 3.0, 4.0)),
+            Colour::new(1.0, 1.5, 2.0)
+        );
+    }
+
+    #[test]
+    fn pattern_translation() {
+        let mut p = TestPattern::default();
+        p.set_transform(Transformation::translation(0.5, 1.0, 1.5));
+
+        assert_eq!(
+            p.colour_at(Point3D::new(2.5, 3.0, 3.5)),
+            Colour::new(2.0, 2.0, 2.0)
+        );
+    }
+}
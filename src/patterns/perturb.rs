@@ -0,0 +1,102 @@
+use super::perlin::noise;
+use super::{Pattern, Placement};
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+
+/// Another pattern with its lookup point jittered by Perlin noise, so
+/// straight lines wander. Perturbed stripes and rings give marble and
+/// wood grain.
+#[derive(Debug)]
+pub struct Perturb {
+    placement: Placement,
+    pattern: Box<dyn Pattern>,
+    scale: f64,
+}
+
+impl Perturb {
+    /// `scale` is the furthest the point may move along each axis.
+    pub fn new<P: Pattern + 'static>(pattern: P, scale: f64) -> Self {
+        Perturb {
+            placement: Placement::default(),
+            pattern: Box::new(pattern),
+            scale,
+        }
+    }
+
+    pub fn pattern(&self) -> &dyn Pattern {
+        &*self.pattern
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale
+    }
+}
+
+impl Pattern for Perturb {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour {
+        let (x, y, z) = (point.x(), point.y(), point.z());
+        // Sampling the noise at offset positions gives each axis its
+        // own, unrelated jitter.
+        let jittered = Point3D::new(
+            x + noise(x, y, z) * self.scale,
+            y + noise(x, y, z + 1.0) * self.scale,
+            z + noise(x, y, z + 2.0) * self.scale,
+        );
+        self.pattern.colour_at(jittered)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::patterns::{Stripe, TestPattern};
+
+    #[test]
+    fn no_scale_no_change() {
+        let p = Perturb::new(TestPattern::default(), 0.0);
+
+        assert_eq!(
+            p.local_colour_at(Point3D::new(0.3, 1.7, -2.2)),
+            Colour::new(0.3, 1.7, -2.2)
+        );
+    }
+
+    #[test]
+    fn jitter_is_bounded() {
+        let p = Perturb::new(TestPattern::default(), 0.2);
+
+        for i in 0..100 {
+            let t = i as f64 * 0.37;
+            let c = p.local_colour_at(Point3D::new(t, -t, t * 0.5));
+
+            assert!((c.red() - t).abs() <= 0.2);
+            assert!((c.green() + t).abs() <= 0.2);
+            assert!((c.blue() - t * 0.5).abs() <= 0.2);
+        }
+    }
+
+    #[test]
+    fn stripes_wander() {
+        let p = Perturb::new(Stripe::new(Colour::white(), Colour::black()), 0.5);
+        // Along a line just inside the edge of a stripe, the noise
+        // carries some points across it.
+        let colours: Vec<Colour> = (0..50)
+            .map(|i| p.local_colour_at(Point3D::new(0.95, 0.0, i as f64 * 0.23)))
+            .collect();
+
+        assert!(colours.contains(&Colour::white()));
+        assert!(colours.contains(&Colour::black()));
+    }
+}
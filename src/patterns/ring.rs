@@ -0,0 +1,75 @@
+use super::{Pattern, Placement, Solid};
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+
+/// Concentric rings of two colours around the y axis, one unit apart.
+#[derive(Debug)]
+pub struct Ring {
+    placement: Placement,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
+}
+
+impl Ring {
+    pub fn new(a: Colour, b: Colour) -> Self {
+        Ring::nested(Solid::new(a), Solid::new(b))
+    }
+
+    /// Uses patterns in place of each colour. They are looked up with
+    /// points in this pattern's space, then apply their own transforms.
+    pub fn nested<A: Pattern + 'static, B: Pattern + 'static>(a: A, b: B) -> Self {
+        Ring {
+            placement: Placement::default(),
+            a: Box::new(a),
+            b: Box::new(b),
+        }
+    }
+
+    pub fn a(&self) -> &dyn Pattern {
+        &*self.a
+    }
+
+    pub fn b(&self) -> &dyn Pattern {
+        &*self.b
+    }
+}
+
+impl Pattern for Ring {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour {
+        let distance = (point.x().powi(2) + point.z().powi(2)).sqrt();
+        if (distance.floor() as i64).rem_euclid(2) == 0 {
+            self.a.colour_at(point)
+        } else {
+            self.b.colour_at(point)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extends_in_x_and_z() {
+        let p = Ring::new(Colour::white(), Colour::black());
+        let cases = [
+            (Point3D::new(0.0, 0.0, 0.0), Colour::white()),
+            (Point3D::new(1.0, 0.0, 0.0), Colour::black()),
+            (Point3D::new(0.0, 0.0, 1.0), Colour::black()),
+            // Just slightly more than √2/2.
+            (Point3D::new(0.708, 0.0, 0.708), Colour::black()),
+        ];
+
+        for (point, colour) in cases.iter() {
+            assert_eq!(p.local_colour_at(*point), *colour);
+        }
+    }
+}
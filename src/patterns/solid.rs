@@ -0,0 +1,53 @@
+use super::{Pattern, Placement};
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+
+/// The same colour everywhere. Mostly useful as part of another
+/// pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solid {
+    placement: Placement,
+    colour: Colour,
+}
+
+impl Solid {
+    pub fn new(colour: Colour) -> Self {
+        Solid {
+            placement: Placement::default(),
+            colour,
+        }
+    }
+
+    pub fn colour(&self) -> Colour {
+        self.colour
+    }
+}
+
+impl Pattern for Solid {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, _point: Point3D<f64>) -> Colour {
+        self.colour
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn constant_everywhere() {
+        let p = Solid::new(Colour::new(0.2, 0.4, 0.6));
+
+        assert_eq!(
+            p.local_colour_at(Point3D::new(-3.0, 7.5, 0.2)),
+            Colour::new(0.2, 0.4, 0.6)
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use super::{Pattern, Placement, Solid};
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+
+/// Alternating bands of two colours, one unit wide, along the x axis.
+#[derive(Debug)]
+pub struct Stripe {
+    placement: Placement,
+    a: Box<dyn Pattern>,
+    b: Box<dyn Pattern>,
+}
+
+impl Stripe {
+    pub fn new(a: Colour, b: Colour) -> Self {
+        Stripe::nested(Solid::new(a), Solid::new(b))
+    }
+
+    /// Uses patterns in place of each colour. They are looked up with
+    /// points in this pattern's space, then apply their own transforms.
+    pub fn nested<A: Pattern + 'static, B: Pattern + 'static>(a: A, b: B) -> Self {
+        Stripe {
+            placement: Placement::default(),
+            a: Box::new(a),
+            b: Box::new(b),
+        }
+    }
+
+    pub fn a(&self) -> &dyn Pattern {
+        &*self.a
+    }
+
+    pub fn b(&self) -> &dyn Pattern {
+        &*self.b
+    }
+}
+
+impl Pattern for Stripe {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour {
+        if (point.x().floor() as i64).rem_euclid(2) == 0 {
+            self.a.colour_at(point)
+        } else {
+            self.b.colour_at(point)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transformations::Transformation;
+
+    fn stripes() -> Stripe {
+        Stripe::new(Colour::white(), Colour::black())
+    }
+
+    #[test]
+    fn construct_stripes() {
+        let p = stripes();
+
+        let origin = Point3D::new(0.0, 0.0, 0.0);
+
+        assert_eq!(p.a().colour_at(origin), Colour::white());
+        assert_eq!(p.b().colour_at(origin), Colour::black());
+    }
+
+    #[test]
+    fn constant_in_y_and_z() {
+        let p = stripes();
+
+        for point in [
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(0.0, 1.0, 0.0),
+            Point3D::new(0.0, 2.0, 0.0),
+            Point3D::new(0.0, 0.0, 1.0),
+            Point3D::new(0.0, 0.0, 2.0),
+        ]
+        .iter()
+        {
+            assert_eq!(p.local_colour_at(*point), Colour::white());
+        }
+    }
+
+    #[test]
+    fn alternates_in_x() {
+        let p = stripes();
+        let cases = [
+            (0.0, Colour::white()),
+            (0.9, Colour::white()),
+            (1.0, Colour::black()),
+            (-0.1, Colour::black()),
+            (-1.0, Colour::black()),
+            (-1.1, Colour::white()),
+        ];
+
+        for (x, colour) in cases.iter() {
+            assert_eq!(p.local_colour_at(Point3D::new(*x, 0.0, 0.0)), *colour);
+        }
+    }
+
+    #[test]
+    fn transformed_stripes() {
+        let mut p = stripes();
+        p.set_transform(Transformation::scaling(2.0, 2.0, 2.0));
+
+        assert_eq!(p.colour_at(Point3D::new(1.5, 0.0, 0.0)), Colour::white());
+    }
+
+    #[test]
+    fn nested_patterns() {
+        let red = Colour::new(1.0, 0.0, 0.0);
+        let mut inner = Stripe::new(red, Colour::black());
+        inner.set_transform(Transformation::rotation_y(std::f64::consts::PI / 2.0));
+        let p = Stripe::nested(inner, Solid::new(Colour::white()));
+
+        // Inside the first stripe, the rotated inner stripes run along z.
+        assert_eq!(p.colour_at(Point3D::new(0.5, 0.0, -0.5)), red);
+        assert_eq!(p.colour_at(Point3D::new(0.5, 0.0, 0.5)), Colour::black());
+        assert_eq!(p.colour_at(Point3D::new(1.5, 0.0, 0.5)), Colour::white());
+    }
+}
@@ -0,0 +1,277 @@
+use super::{Pattern, Placement};
+use crate::canvas::Canvas;
+use crate::colour::Colour;
+use crate::descartes::Point3D;
+use std::f64::consts::PI;
+
+/// A two dimensional pattern, coloured by `u` and `v` coordinates which
+/// each run from 0 to 1.
+pub trait UvPattern: std::fmt::Debug {
+    fn uv_colour_at(&self, u: f64, v: f64) -> Colour;
+}
+
+/// Ways of wrapping a flat `UvPattern` around a shape.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum UvMapping {
+    /// Around a unit sphere, like a map of the earth: u follows the
+    /// longitude and v the latitude.
+    Spherical,
+    /// Tiled across the xz plane, repeating every unit.
+    Planar,
+    /// Around a unit cylinder, repeating every unit along y.
+    Cylindrical,
+}
+
+impl UvMapping {
+    pub fn map(self, point: Point3D<f64>) -> (f64, f64) {
+        match self {
+            UvMapping::Spherical => {
+                let radius = (point.x().powi(2) + point.y().powi(2) + point.z().powi(2)).sqrt();
+                let phi = (point.y() / radius).acos();
+                (azimuth_u(point), 1.0 - phi / PI)
+            }
+            UvMapping::Planar => (point.x().rem_euclid(1.0), point.z().rem_euclid(1.0)),
+            UvMapping::Cylindrical => (azimuth_u(point), point.y().rem_euclid(1.0)),
+        }
+    }
+}
+
+/// How far around the y axis a point is, from 0 to 1, increasing
+/// anticlockwise when seen from above.
+fn azimuth_u(point: Point3D<f64>) -> f64 {
+    let theta = point.x().atan2(point.z());
+    let raw_u = theta / (2.0 * PI);
+    1.0 - (raw_u + 0.5)
+}
+
+/// A `UvPattern` wrapped around a shape by one of the mappings.
+#[derive(Debug)]
+pub struct TextureMap {
+    placement: Placement,
+    mapping: UvMapping,
+    uv_pattern: Box<dyn UvPattern>,
+}
+
+impl TextureMap {
+    pub fn new<P: UvPattern + 'static>(mapping: UvMapping, uv_pattern: P) -> Self {
+        TextureMap {
+            placement: Placement::default(),
+            mapping,
+            uv_pattern: Box::new(uv_pattern),
+        }
+    }
+
+    pub fn mapping(&self) -> UvMapping {
+        self.mapping
+    }
+
+    pub fn uv_pattern(&self) -> &dyn UvPattern {
+        &*self.uv_pattern
+    }
+}
+
+impl Pattern for TextureMap {
+    fn placement(&self) -> &Placement {
+        &self.placement
+    }
+
+    fn placement_mut(&mut self) -> &mut Placement {
+        &mut self.placement
+    }
+
+    fn local_colour_at(&self, point: Point3D<f64>) -> Colour {
+        let (u, v) = self.mapping.map(point);
+        self.uv_pattern.uv_colour_at(u, v)
+    }
+}
+
+/// A grid of `width` by `height` alternating squares, mostly for
+/// checking how a mapping lays out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UvChecker {
+    width: f64,
+    height: f64,
+    a: Colour,
+    b: Colour,
+}
+
+impl UvChecker {
+    pub fn new(width: f64, height: f64, a: Colour, b: Colour) -> Self {
+        UvChecker {
+            width,
+            height,
+            a,
+            b,
+        }
+    }
+}
+
+impl UvPattern for UvChecker {
+    fn uv_colour_at(&self, u: f64, v: f64) -> Colour {
+        let sum = (u * self.width).floor() + (v * self.height).floor();
+        if (sum as i64).rem_euclid(2) == 0 {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+/// An image stretched over the whole of the u/v square, with v = 1 at
+/// the top row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UvImage {
+    canvas: Canvas,
+}
+
+impl UvImage {
+    pub fn new(canvas: Canvas) -> Self {
+        UvImage { canvas }
+    }
+
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+}
+
+impl UvPattern for UvImage {
+    /// Takes the nearest pixel, without any filtering.
+    fn uv_colour_at(&self, u: f64, v: f64) -> Colour {
+        // Image rows run down the page, but v runs up it.
+        let v = 1.0 - v;
+        let x = (u * (self.canvas.width() - 1) as f64).round();
+        let y = (v * (self.canvas.height() - 1) as f64).round();
+        self.canvas.get_pixel(x as usize, y as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checker_pattern() {
+        let p = UvChecker::new(2.0, 2.0, Colour::black(), Colour::white());
+        let cases = [
+            (0.0, 0.0, Colour::black()),
+            (0.5, 0.0, Colour::white()),
+            (0.0, 0.5, Colour::white()),
+            (0.5, 0.5, Colour::black()),
+            (1.0, 1.0, Colour::black()),
+        ];
+
+        for (u, v, colour) in cases.iter() {
+            assert_eq!(p.uv_colour_at(*u, *v), *colour);
+        }
+    }
+
+    fn assert_uv(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-5 && (actual.1 - expected.1).abs() < 1e-5,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn spherical_mapping() {
+        let k = 2.0_f64.sqrt() / 2.0;
+        let cases = [
+            (Point3D::new(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (Point3D::new(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (Point3D::new(0.0, 0.0, 1.0), (0.5, 0.5)),
+            (Point3D::new(-1.0, 0.0, 0.0), (0.75, 0.5)),
+            (Point3D::new(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (Point3D::new(0.0, -1.0, 0.0), (0.5, 0.0)),
+            (Point3D::new(k, k, 0.0), (0.25, 0.75)),
+        ];
+
+        for (point, uv) in cases.iter() {
+            assert_uv(UvMapping::Spherical.map(*point), *uv);
+        }
+    }
+
+    #[test]
+    fn planar_mapping() {
+        let cases = [
+            (Point3D::new(0.25, 0.0, 0.5), (0.25, 0.5)),
+            (Point3D::new(0.25, 0.0, -0.25), (0.25, 0.75)),
+            (Point3D::new(0.25, 0.5, -0.25), (0.25, 0.75)),
+            (Point3D::new(1.25, 0.0, 0.5), (0.25, 0.5)),
+            (Point3D::new(0.25, 0.0, -1.75), (0.25, 0.25)),
+            (Point3D::new(1.0, 0.0, -1.0), (0.0, 0.0)),
+            (Point3D::new(0.0, 0.0, 0.0), (0.0, 0.0)),
+        ];
+
+        for (point, uv) in cases.iter() {
+            assert_uv(UvMapping::Planar.map(*point), *uv);
+        }
+    }
+
+    #[test]
+    fn cylindrical_mapping() {
+        let k = 2.0_f64.sqrt() / 2.0;
+        let cases = [
+            (Point3D::new(0.0, 0.0, -1.0), (0.0, 0.0)),
+            (Point3D::new(0.0, 0.5, -1.0), (0.0, 0.5)),
+            (Point3D::new(0.0, 1.0, -1.0), (0.0, 0.0)),
+            (Point3D::new(k, 0.5, -k), (0.125, 0.5)),
+            (Point3D::new(1.0, 0.5, 0.0), (0.25, 0.5)),
+            (Point3D::new(k, 0.5, k), (0.375, 0.5)),
+            (Point3D::new(0.0, -0.25, 1.0), (0.5, 0.75)),
+            (Point3D::new(-k, 0.5, k), (0.625, 0.5)),
+            (Point3D::new(-1.0, 1.25, 0.0), (0.75, 0.25)),
+            (Point3D::new(-k, 0.5, -k), (0.875, 0.5)),
+        ];
+
+        for (point, uv) in cases.iter() {
+            assert_uv(UvMapping::Cylindrical.map(*point), *uv);
+        }
+    }
+
+    #[test]
+    fn texture_map_on_sphere() {
+        let checkers = UvChecker::new(16.0, 8.0, Colour::black(), Colour::white());
+        let p = TextureMap::new(UvMapping::Spherical, checkers);
+        let cases = [
+            (Point3D::new(0.4315, 0.4670, 0.7719), Colour::white()),
+            (Point3D::new(-0.9654, 0.2552, -0.0534), Colour::black()),
+            (Point3D::new(0.1039, 0.7090, 0.6975), Colour::white()),
+            (Point3D::new(-0.4986, -0.7856, -0.3663), Colour::black()),
+            (Point3D::new(-0.0317, -0.9395, 0.3411), Colour::black()),
+            (Point3D::new(0.4809, -0.7721, 0.4154), Colour::black()),
+            (Point3D::new(0.0285, -0.9612, -0.2745), Colour::black()),
+            (Point3D::new(-0.5734, -0.2162, -0.7903), Colour::white()),
+            (Point3D::new(0.7688, -0.1470, 0.6223), Colour::black()),
+            (Point3D::new(-0.7652, 0.2175, 0.6060), Colour::black()),
+        ];
+
+        for (point, colour) in cases.iter() {
+            assert_eq!(p.colour_at(*point), *colour, "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn image_pattern() {
+        // A 10x10 ramp where each pixel's grey level is its position,
+        // counting from the top left.
+        let mut canvas = Canvas::new(10, 10);
+        for i in 0..100 {
+            let level = i as f64 / 99.0;
+            canvas.write_pixel(i % 10, i / 10, Colour::new(level, level, level));
+        }
+        let p = UvImage::new(canvas);
+        let grey = |level: f64| Colour::new(level / 99.0, level / 99.0, level / 99.0);
+        let cases = [
+            (0.0, 0.0, 90.0),
+            (0.3, 0.0, 93.0),
+            (0.6, 0.3, 65.0),
+            (1.0, 1.0, 9.0),
+        ];
+
+        for (u, v, level) in cases.iter() {
+            assert_eq!(p.uv_colour_at(*u, *v), grey(*level));
+        }
+    }
+}
@@ -1,4 +1,6 @@
 use crate::descartes::{Point3D, Vector3D};
+use crate::matrix::M4;
+use crate::shapes::{Cube, Plane, Shape, Sphere};
 use typed_arena::Arena;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -12,23 +14,23 @@ impl Ray {
         Ray { origin, direction }
     }
 
-    pub fn position(&self, t: f64) -> Point3D<f64> {
-        self.origin + (self.direction * t)
+    pub fn origin(&self) -> Point3D<f64> {
+        self.origin
     }
-}
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Sphere {
-    id: usize,
-}
+    pub fn direction(&self) -> Vector3D<f64> {
+        self.direction
+    }
 
-impl Sphere {
-    pub fn new(id: usize) -> Self {
-        Self { id }
+    pub fn position(&self, t: f64) -> Point3D<f64> {
+        self.origin + (self.direction * t)
     }
 
-    pub fn id(&self) -> usize {
-        self.id
+    /// The direction is deliberately left unnormalized, so that `t`
+    /// values found against the transformed ray are also valid along
+    /// the original one.
+    pub fn transform(&self, matrix: M4) -> Self {
+        Ray::new(matrix * self.origin, matrix * self.direction)
     }
 }
 
@@ -36,11 +38,115 @@ impl Sphere {
 pub struct Intersection {
     t: f64,
     object: usize,
+    uv: Option<(f64, f64)>,
 }
 
 impl Intersection {
     pub fn new(t: f64, object: usize) -> Self {
-        Self { t, object }
+        Self {
+            t,
+            object,
+            uv: None,
+        }
+    }
+
+    /// An intersection which also records where on the surface it
+    /// occurred, e.g. barycentric coordinates on a triangle.
+    pub fn with_uv(t: f64, object: usize, u: f64, v: f64) -> Self {
+        Self {
+            t,
+            object,
+            uv: Some((u, v)),
+        }
+    }
+
+    pub fn uv(&self) -> Option<(f64, f64)> {
+        self.uv
+    }
+
+    pub fn t(&self) -> f64 {
+        self.t
+    }
+
+    pub fn object(&self) -> usize {
+        self.object
+    }
+}
+
+/// A list of intersections which is always kept sorted by `t`, however
+/// it was assembled.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Intersections(Vec<Intersection>);
+
+impl Intersections {
+    pub fn new() -> Self {
+        Intersections(Vec::new())
+    }
+
+    pub fn push(&mut self, intersection: Intersection) {
+        // Inserting after any equal `t` keeps the order stable.
+        let idx = self.0.partition_point(|i| i.t <= intersection.t);
+        self.0.insert(idx, intersection)
+    }
+
+    pub fn merge(&mut self, other: Intersections) {
+        for intersection in other.0 {
+            self.push(intersection)
+        }
+    }
+
+    /// The visible intersection: the one with the lowest non-negative
+    /// `t`. Anything negative is behind the ray's origin.
+    pub fn hit(&self) -> Option<&Intersection> {
+        self.0.iter().find(|i| i.t >= 0.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Intersection> {
+        self.0.iter()
+    }
+}
+
+impl std::ops::Index<usize> for Intersections {
+    type Output = Intersection;
+
+    fn index(&self, idx: usize) -> &Intersection {
+        &self.0[idx]
+    }
+}
+
+impl From<Vec<Intersection>> for Intersections {
+    fn from(intersections: Vec<Intersection>) -> Self {
+        let mut result = Intersections::new();
+        for intersection in intersections {
+            result.push(intersection)
+        }
+        result
+    }
+}
+
+impl From<Interaction> for Intersections {
+    fn from(interaction: Interaction) -> Self {
+        match interaction {
+            Interaction::Collision(intersections) => intersections.into(),
+            Interaction::Miss => Intersections::new(),
+        }
+    }
+}
+
+impl IntoIterator for Intersections {
+    type Item = Intersection;
+    type IntoIter = std::vec::IntoIter<Intersection>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
     }
 }
 
@@ -51,26 +157,22 @@ pub enum Interaction {
 }
 
 impl Interaction {
-    pub fn new(sphere: &mut Sphere, ray: Ray) -> Self {
-        let ro = ray.origin - Point3D::new(0.0, 0.0, 0.0);
-        let a = ray.direction.dot(ray.direction);
-        let b = 2.0 * ray.direction.dot(ro);
-        let c = ro.dot(ro) - 1.0;
-        let d = b.powi(2) - (4.0 * a * c);
-
-        if d < 0.0 {
-            return Interaction::Miss;
-        }
+    pub fn new(shape: &dyn Shape, ray: Ray) -> Self {
+        shape.intersect(&ray)
+    }
+}
 
-        Interaction::Collision(vec![
-            Intersection::new((-b - d.sqrt()) / (2.0 * a), sphere.id()),
-            Intersection::new((-b + d.sqrt()) / (2.0 * a), sphere.id()),
-        ])
+impl Default for Scene {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// An arena of shapes under construction. Every shape is handed its
+/// id as it is allocated, and references to it stay valid while more
+/// are added.
 pub struct Scene {
-    objects: Arena<Sphere>,
+    objects: Arena<Box<dyn Shape>>,
 }
 
 impl Scene {
@@ -80,16 +182,38 @@ impl Scene {
         }
     }
 
-    pub fn sphere(&self) -> &mut Sphere {
-        let new_id = self.objects.len();
-        let s = self.objects.alloc(Sphere::new(new_id));
-        s
+    pub fn add<S: Shape + 'static>(&self, shape: S) -> &mut dyn Shape {
+        self.add_boxed(Box::new(shape))
+    }
+
+    pub fn add_boxed(&self, mut shape: Box<dyn Shape>) -> &mut dyn Shape {
+        shape.set_id(self.objects.len());
+        &mut **self.objects.alloc(shape)
+    }
+
+    pub fn sphere(&self) -> &mut dyn Shape {
+        self.add(Sphere::new())
+    }
+
+    pub fn plane(&self) -> &mut dyn Shape {
+        self.add(Plane::new())
+    }
+
+    pub fn cube(&self) -> &mut dyn Shape {
+        self.add(Cube::new())
+    }
+
+    /// Consumes the scene, returning its objects in allocation order so
+    /// that each object's id is also its index.
+    pub fn into_objects(self) -> Vec<Box<dyn Shape>> {
+        self.objects.into_vec()
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::transformations::Transformation;
     #[test]
     fn ray_position() {
         let r = Ray::new(Point3D::new(2.0, 3.0, 4.0), Vector3D::new(1.0, 0.0, 0.0));
@@ -100,6 +224,24 @@ mod test {
         assert_eq!(r.position(2.5), Point3D::new(4.5, 3.0, 4.0));
     }
 
+    #[test]
+    fn translate_ray() {
+        let r = Ray::new(Point3D::new(1.0, 2.0, 3.0), Vector3D::new(0.0, 1.0, 0.0));
+        let t = r.transform(M4::translation(3.0, 4.0, 5.0));
+
+        assert_eq!(t.origin(), Point3D::new(4.0, 6.0, 8.0));
+        assert_eq!(t.direction(), Vector3D::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn scale_ray() {
+        let r = Ray::new(Point3D::new(1.0, 2.0, 3.0), Vector3D::new(0.0, 1.0, 0.0));
+        let t = r.transform(M4::scaling(2.0, 3.0, 4.0));
+
+        assert_eq!(t.origin(), Point3D::new(2.0, 6.0, 12.0));
+        assert_eq!(t.direction(), Vector3D::new(0.0, 3.0, 0.0));
+    }
+
     #[test]
     fn equatorial_interaction() {
         let scene = Scene::new();
@@ -184,6 +326,123 @@ mod test {
         )
     }
 
+    #[test]
+    fn scaled_sphere_interaction() {
+        let scene = Scene::new();
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let s = scene.sphere();
+        s.set_transform(Transformation::scaling(2.0, 2.0, 2.0));
+        let i = Interaction::new(s, r);
+
+        assert_eq!(
+            i,
+            Interaction::Collision(vec![
+                Intersection::new(3.0, s.id()),
+                Intersection::new(7.0, s.id())
+            ])
+        )
+    }
+
+    #[test]
+    fn translated_sphere_interaction() {
+        let scene = Scene::new();
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let s = scene.sphere();
+        s.set_transform(Transformation::translation(5.0, 0.0, 0.0));
+        let i = Interaction::new(s, r);
+
+        assert_eq!(i, Interaction::Miss)
+    }
+
+    #[test]
+    fn intersection_accessors() {
+        let i = Intersection::new(3.5, 7);
+
+        assert_eq!(i.t(), 3.5);
+        assert_eq!(i.object(), 7);
+        assert_eq!(i.uv(), None);
+    }
+
+    #[test]
+    fn intersection_with_uv() {
+        let i = Intersection::with_uv(3.5, 7, 0.2, 0.4);
+
+        assert_eq!(i.t(), 3.5);
+        assert_eq!(i.uv(), Some((0.2, 0.4)));
+    }
+
+    #[test]
+    fn intersections_stay_sorted() {
+        let xs = Intersections::from(vec![
+            Intersection::new(5.0, 0),
+            Intersection::new(7.0, 0),
+            Intersection::new(-3.0, 0),
+            Intersection::new(2.0, 0),
+        ]);
+        let ts: Vec<f64> = xs.iter().map(|i| i.t()).collect();
+
+        assert_eq!(ts, vec![-3.0, 2.0, 5.0, 7.0]);
+    }
+
+    #[test]
+    fn merge_across_objects() {
+        let scene = Scene::new();
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let s1 = scene.sphere();
+        let s2 = scene.sphere();
+        s2.set_transform(Transformation::scaling(2.0, 2.0, 2.0));
+
+        let mut xs = Intersections::from(Interaction::new(s1, r.clone()));
+        xs.merge(Interaction::new(s2, r).into());
+
+        assert_eq!(xs.len(), 4);
+        assert_eq!(xs[0], Intersection::new(3.0, 1));
+        assert_eq!(xs[1], Intersection::new(4.0, 0));
+        assert_eq!(xs[2], Intersection::new(6.0, 0));
+        assert_eq!(xs[3], Intersection::new(7.0, 1));
+    }
+
+    #[test]
+    fn hit_all_positive() {
+        let xs = Intersections::from(vec![Intersection::new(2.0, 0), Intersection::new(1.0, 0)]);
+
+        assert_eq!(xs.hit(), Some(&Intersection::new(1.0, 0)));
+    }
+
+    #[test]
+    fn hit_some_negative() {
+        let xs = Intersections::from(vec![Intersection::new(1.0, 0), Intersection::new(-1.0, 0)]);
+
+        assert_eq!(xs.hit(), Some(&Intersection::new(1.0, 0)));
+    }
+
+    #[test]
+    fn hit_all_negative() {
+        let xs = Intersections::from(vec![Intersection::new(-2.0, 0), Intersection::new(-1.0, 0)]);
+
+        assert_eq!(xs.hit(), None);
+    }
+
+    #[test]
+    fn hit_lowest_non_negative() {
+        let xs = Intersections::from(vec![
+            Intersection::new(5.0, 0),
+            Intersection::new(7.0, 1),
+            Intersection::new(-3.0, 2),
+            Intersection::new(2.0, 3),
+        ]);
+
+        assert_eq!(xs.hit(), Some(&Intersection::new(2.0, 3)));
+    }
+
+    #[test]
+    fn miss_has_no_hit() {
+        let xs = Intersections::from(Interaction::Miss);
+
+        assert!(xs.is_empty());
+        assert_eq!(xs.hit(), None);
+    }
+
     #[test]
     fn different_spheres() {
         let scene = Scene::new();
@@ -191,9 +450,9 @@ mod test {
         let s2 = scene.sphere();
         let s3 = scene.sphere();
 
-        assert_ne!(s1, s2);
-        assert_ne!(s2, s3);
-        assert_ne!(s1, s3);
+        assert_ne!(s1.id(), s2.id());
+        assert_ne!(s2.id(), s3.id());
+        assert_ne!(s1.id(), s3.id());
         assert_eq!(scene.objects.len(), 3);
     }
 }
@@ -0,0 +1,278 @@
+use super::cylinder::check_cap;
+use super::{Properties, Shape};
+use crate::bounds::Bounds;
+use crate::descartes::{Point3D, Vector3D};
+use crate::raycasting::{Intersection, Ray};
+use crate::EPSILON;
+
+/// A double-napped cone around the y axis, with its tips meeting at the
+/// origin and a radius equal to the distance along y. Like a cylinder
+/// it can be truncated and capped; a cap's radius is its y position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cone {
+    properties: Properties,
+    minimum: f64,
+    maximum: f64,
+    closed: bool,
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Cone {
+            properties: Properties::default(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Cone {
+    pub fn new() -> Self {
+        Cone::default()
+    }
+
+    pub fn truncated(minimum: f64, maximum: f64, closed: bool) -> Self {
+        Cone {
+            minimum,
+            maximum,
+            closed,
+            ..Cone::default()
+        }
+    }
+
+    pub fn minimum(&self) -> f64 {
+        self.minimum
+    }
+
+    pub fn set_minimum(&mut self, minimum: f64) {
+        self.minimum = minimum
+    }
+
+    pub fn maximum(&self) -> f64 {
+        self.maximum
+    }
+
+    pub fn set_maximum(&mut self, maximum: f64) {
+        self.maximum = maximum
+    }
+
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn set_closed(&mut self, closed: bool) {
+        self.closed = closed
+    }
+}
+
+impl Shape for Cone {
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let (origin, direction) = (ray.origin(), ray.direction());
+        let mut xs = Vec::new();
+
+        let a = direction.x().powi(2) - direction.y().powi(2) + direction.z().powi(2);
+        let b = 2.0 * origin.x() * direction.x() - 2.0 * origin.y() * direction.y()
+            + 2.0 * origin.z() * direction.z();
+        let c = origin.x().powi(2) - origin.y().powi(2) + origin.z().powi(2);
+        // As for cylinders, parallel is judged relative to the length
+        // of the direction, which shrinks as the cone is scaled up.
+        let length = direction.mag();
+
+        if a.abs() < EPSILON * length.powi(2) {
+            // The ray is parallel to one of the cone's halves, so it
+            // crosses the other half at most once.
+            if b.abs() >= EPSILON {
+                let t = -c / (2.0 * b);
+                let y = origin.y() + t * direction.y();
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self.id()))
+                }
+            }
+        } else {
+            let disc = b.powi(2) - 4.0 * a * c;
+            // Rays grazing the tip can produce a tiny negative
+            // discriminant through rounding alone.
+            if disc < -EPSILON {
+                return xs;
+            }
+            let disc = disc.max(0.0);
+
+            let t0 = (-b - disc.sqrt()) / (2.0 * a);
+            let t1 = (-b + disc.sqrt()) / (2.0 * a);
+            for t in [t0.min(t1), t0.max(t1)].iter() {
+                let y = origin.y() + t * direction.y();
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(*t, self.id()))
+                }
+            }
+        }
+
+        if self.closed && direction.y().abs() >= EPSILON * length {
+            for cap in [self.minimum, self.maximum].iter() {
+                let t = (cap - origin.y()) / direction.y();
+                if check_cap(ray, t, cap.abs()) {
+                    xs.push(Intersection::new(t, self.id()))
+                }
+            }
+        }
+
+        xs
+    }
+
+    fn local_normal_at(&self, point: Point3D<f64>, _hit: &Intersection) -> Vector3D<f64> {
+        let dist = point.x().powi(2) + point.z().powi(2);
+
+        if dist < self.maximum.powi(2) && point.y() >= self.maximum - EPSILON {
+            return Vector3D::new(0.0, 1.0, 0.0);
+        }
+        if dist < self.minimum.powi(2) && point.y() <= self.minimum + EPSILON {
+            return Vector3D::new(0.0, -1.0, 0.0);
+        }
+
+        // The side normal shrinks to nothing at the apex, where it is
+        // taken to point straight along the axis, away from the nappe.
+        let y = if point.y() > 0.0 {
+            -dist.sqrt()
+        } else {
+            dist.sqrt()
+        };
+        if dist.sqrt() < EPSILON {
+            return Vector3D::new(0.0, y.signum(), 0.0);
+        }
+        Vector3D::new(point.x(), y, point.z())
+    }
+
+    fn bounds(&self) -> Bounds {
+        let limit = self.minimum.abs().max(self.maximum.abs());
+
+        Bounds::new(
+            Point3D::new(-limit, self.minimum, -limit),
+            Point3D::new(limit, self.maximum, limit),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::raycasting::Interaction;
+    use crate::transformations::Transformation;
+
+    fn ray(origin: (f64, f64, f64), direction: (f64, f64, f64)) -> Ray {
+        Ray::new(
+            Point3D::new(origin.0, origin.1, origin.2),
+            Vector3D::new(direction.0, direction.1, direction.2).normalize(),
+        )
+    }
+
+    #[test]
+    fn ray_strikes_cone() {
+        let c = Cone::new();
+        let cases = [
+            ((0.0, 0.0, -5.0), (0.0, 0.0, 1.0), 5.0, 5.0),
+            ((0.0, 0.0, -5.0), (1.0, 1.0, 1.0), 8.66025, 8.66025),
+            ((1.0, 1.0, -5.0), (-0.5, -1.0, 1.0), 4.55006, 49.44994),
+        ];
+
+        for (origin, direction, t0, t1) in cases.iter() {
+            let xs = c.local_intersect(&ray(*origin, *direction));
+
+            assert_eq!(xs.len(), 2);
+            assert!((xs[0].t() - t0).abs() < 0.0001);
+            assert!((xs[1].t() - t1).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn ray_parallel_to_one_half() {
+        let c = Cone::new();
+        let xs = c.local_intersect(&ray((0.0, 0.0, -1.0), (0.0, 1.0, 1.0)));
+
+        assert_eq!(xs.len(), 1);
+        assert!((xs[0].t() - 0.35355).abs() < EPSILON);
+    }
+
+    #[test]
+    fn capped_cone() {
+        let c = Cone::truncated(-0.5, 0.5, true);
+        let cases = [
+            ((0.0, 0.0, -5.0), (0.0, 1.0, 0.0), 0),
+            ((0.0, 0.0, -0.25), (0.0, 1.0, 1.0), 2),
+            ((0.0, 0.0, -0.25), (0.0, 1.0, 0.0), 4),
+        ];
+
+        for (origin, direction, count) in cases.iter() {
+            assert_eq!(c.local_intersect(&ray(*origin, *direction)).len(), *count);
+        }
+    }
+
+    #[test]
+    fn cone_normal() {
+        let hit = Intersection::new(0.0, 0);
+        let c = Cone::new();
+        let cases = [
+            ((1.0, 1.0, 1.0), (1.0, -(2.0_f64.sqrt()), 1.0)),
+            ((-1.0, -1.0, 0.0), (-1.0, 1.0, 0.0)),
+        ];
+
+        for ((px, py, pz), (nx, ny, nz)) in cases.iter() {
+            assert_eq!(
+                c.local_normal_at(Point3D::new(*px, *py, *pz), &hit),
+                Vector3D::new(*nx, *ny, *nz)
+            );
+        }
+    }
+
+    #[test]
+    fn cone_apex_normal() {
+        let hit = Intersection::new(0.0, 0);
+        let c = Cone::new();
+        let cases = [
+            ((0.0, 0.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, -0.000001, 0.0), (0.0, 1.0, 0.0)),
+            ((0.000001, 0.000001, 0.0), (0.0, -1.0, 0.0)),
+        ];
+
+        for ((px, py, pz), (nx, ny, nz)) in cases.iter() {
+            assert_eq!(
+                c.local_normal_at(Point3D::new(*px, *py, *pz), &hit),
+                Vector3D::new(*nx, *ny, *nz)
+            );
+        }
+    }
+
+    #[test]
+    fn cone_bounds() {
+        let b = Cone::truncated(-5.0, 3.0, false).bounds();
+
+        assert_eq!(b.min(), Point3D::new(-5.0, -5.0, -5.0));
+        assert_eq!(b.max(), Point3D::new(5.0, 3.0, 5.0));
+    }
+
+    #[test]
+    fn scaled_cone() {
+        let mut c = Cone::new();
+        c.set_transform(Transformation::scaling(500.0, 500.0, 500.0));
+        let r = Ray::new(
+            Point3D::new(0.0, 250.0, -1500.0),
+            Vector3D::new(0.0, 0.0, 1.0),
+        );
+        let xs = match c.intersect(&r) {
+            Interaction::Collision(xs) => xs,
+            Interaction::Miss => panic!("ray missed the cone"),
+        };
+
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0].t() - 1250.0).abs() < EPSILON);
+        assert!((xs[1].t() - 1750.0).abs() < EPSILON);
+    }
+}
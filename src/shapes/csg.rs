@@ -0,0 +1,203 @@
+use super::{Properties, Shape};
+use crate::bounds::Bounds;
+use crate::descartes::{Point3D, Vector3D};
+use crate::raycasting::{Intersection, Intersections, Ray};
+
+/// How the two halves of a `Csg` are combined.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CsgOperation {
+    /// Everything within either shape.
+    Union,
+    /// Only what is within both shapes.
+    Intersection,
+    /// The first shape with the second carved out of it.
+    Difference,
+}
+
+impl CsgOperation {
+    /// Whether a hit on one half lies on the surface of the combined
+    /// shape. `left_hit` says which half was hit, while `in_left` and
+    /// `in_right` say whether the ray is currently inside each half.
+    pub fn allows(self, left_hit: bool, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => (left_hit && !in_right) || (!left_hit && !in_left),
+            CsgOperation::Intersection => (left_hit && in_right) || (!left_hit && in_left),
+            CsgOperation::Difference => (left_hit && !in_right) || (!left_hit && in_left),
+        }
+    }
+}
+
+/// Two shapes combined by a set operation. Like a group it only holds
+/// the ids of its halves, which a `World` intersects on its behalf.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Csg {
+    properties: Properties,
+    operation: CsgOperation,
+    children: [usize; 2],
+}
+
+impl Csg {
+    /// Combines the shapes with ids `left` and `right`; they become its
+    /// children when it is added to a world.
+    pub fn new(operation: CsgOperation, left: usize, right: usize) -> Self {
+        Csg {
+            properties: Properties::default(),
+            operation,
+            children: [left, right],
+        }
+    }
+
+    pub fn operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    pub fn left(&self) -> usize {
+        self.children[0]
+    }
+
+    pub fn right(&self) -> usize {
+        self.children[1]
+    }
+}
+
+impl Shape for Csg {
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    /// The surface is entirely that of the halves, which are intersected
+    /// separately by the world.
+    fn local_intersect(&self, _ray: &Ray) -> Vec<Intersection> {
+        Vec::new()
+    }
+
+    /// Panics, as hits are always on one of the halves.
+    fn local_normal_at(&self, _point: Point3D<f64>, _hit: &Intersection) -> Vector3D<f64> {
+        panic!("CSG shapes have no surface of their own, so no normal")
+    }
+
+    /// The halves aren't visible from here, so this is an empty box.
+    fn bounds(&self) -> Bounds {
+        Bounds::empty()
+    }
+
+    fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Walks the intersections in order, tracking whether the ray is
+    /// inside each half, and keeps only those the operation allows.
+    fn filter_intersections(
+        &self,
+        xs: Intersections,
+        in_first_child: &dyn Fn(usize) -> bool,
+    ) -> Intersections {
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut result = Intersections::new();
+
+        for i in xs {
+            let left_hit = in_first_child(i.object());
+            if self.operation.allows(left_hit, in_left, in_right) {
+                result.push(i);
+            }
+
+            if left_hit {
+                in_left = !in_left;
+            } else {
+                in_right = !in_right;
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn construct_csg() {
+        let c = Csg::new(CsgOperation::Union, 1, 2);
+
+        assert_eq!(c.operation(), CsgOperation::Union);
+        assert_eq!(c.left(), 1);
+        assert_eq!(c.right(), 2);
+        assert_eq!(c.children(), &[1, 2]);
+    }
+
+    #[test]
+    fn operation_rules() {
+        use CsgOperation::*;
+
+        // (operation, left_hit, in_left, in_right, allowed)
+        let cases = [
+            (Union, true, true, true, false),
+            (Union, true, true, false, true),
+            (Union, true, false, true, false),
+            (Union, true, false, false, true),
+            (Union, false, true, true, false),
+            (Union, false, true, false, false),
+            (Union, false, false, true, true),
+            (Union, false, false, false, true),
+            (Intersection, true, true, true, true),
+            (Intersection, true, true, false, false),
+            (Intersection, true, false, true, true),
+            (Intersection, true, false, false, false),
+            (Intersection, false, true, true, true),
+            (Intersection, false, true, false, true),
+            (Intersection, false, false, true, false),
+            (Intersection, false, false, false, false),
+            (Difference, true, true, true, false),
+            (Difference, true, true, false, true),
+            (Difference, true, false, true, false),
+            (Difference, true, false, false, true),
+            (Difference, false, true, true, true),
+            (Difference, false, true, false, true),
+            (Difference, false, false, true, false),
+            (Difference, false, false, false, false),
+        ];
+
+        for (op, left_hit, in_left, in_right, allowed) in cases.iter() {
+            assert_eq!(
+                op.allows(*left_hit, *in_left, *in_right),
+                *allowed,
+                "{:?} {} {} {}",
+                op,
+                left_hit,
+                in_left,
+                in_right
+            );
+        }
+    }
+
+    #[test]
+    fn filter_intersections() {
+        let cases = [
+            (CsgOperation::Union, 0, 3),
+            (CsgOperation::Intersection, 1, 2),
+            (CsgOperation::Difference, 0, 1),
+        ];
+
+        for (op, first, second) in cases.iter() {
+            let c = Csg::new(*op, 1, 2);
+            let xs: Intersections = vec![
+                Intersection::new(1.0, 1),
+                Intersection::new(2.0, 2),
+                Intersection::new(3.0, 1),
+                Intersection::new(4.0, 2),
+            ]
+            .into();
+            let result = c.filter_intersections(xs.clone(), &|id| id == 1);
+
+            assert_eq!(result.len(), 2);
+            assert_eq!(result[0], xs[*first]);
+            assert_eq!(result[1], xs[*second]);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use super::{Properties, Shape};
+use crate::bounds::Bounds;
+use crate::descartes::{Point3D, Vector3D};
+use crate::raycasting::{Intersection, Ray};
+use crate::EPSILON;
+
+/// An axis-aligned cube centred on the origin, with faces at -1 and 1
+/// along each axis.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Cube {
+    properties: Properties,
+}
+
+impl Cube {
+    pub fn new() -> Self {
+        Cube::default()
+    }
+}
+
+/// The range of `t` for which the ray lies between the pair of planes
+/// at -1 and 1 along a single axis.
+fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
+    let tmin_numerator = -1.0 - origin;
+    let tmax_numerator = 1.0 - origin;
+
+    // A ray parallel to the slab either lies between its planes
+    // forever or never does; infinities give the right answer for
+    // both when combined with the other axes.
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+impl Shape for Cube {
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let (origin, direction) = (ray.origin(), ray.direction());
+        let (xtmin, xtmax) = check_axis(origin.x(), direction.x());
+        let (ytmin, ytmax) = check_axis(origin.y(), direction.y());
+        let (ztmin, ztmax) = check_axis(origin.z(), direction.z());
+
+        // The ray is inside the cube only while it is inside all three
+        // slabs at once.
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            return Vec::new();
+        }
+
+        vec![
+            Intersection::new(tmin, self.id()),
+            Intersection::new(tmax, self.id()),
+        ]
+    }
+
+    /// Whichever component has the largest magnitude tells us which
+    /// face the point is on.
+    fn local_normal_at(&self, point: Point3D<f64>, _hit: &Intersection) -> Vector3D<f64> {
+        let (x, y, z) = (point.x(), point.y(), point.z());
+        let maxc = x.abs().max(y.abs()).max(z.abs());
+
+        if maxc == x.abs() {
+            Vector3D::new(x, 0.0, 0.0)
+        } else if maxc == y.abs() {
+            Vector3D::new(0.0, y, 0.0)
+        } else {
+            Vector3D::new(0.0, 0.0, z)
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(Point3D::new(-1.0, -1.0, -1.0), Point3D::new(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::raycasting::Interaction;
+
+    #[test]
+    fn ray_hits_each_face() {
+        let c = Cube::new();
+        let cases = [
+            ((5.0, 0.5, 0.0), (-1.0, 0.0, 0.0), 4.0, 6.0),
+            ((-5.0, 0.5, 0.0), (1.0, 0.0, 0.0), 4.0, 6.0),
+            ((0.5, 5.0, 0.0), (0.0, -1.0, 0.0), 4.0, 6.0),
+            ((0.5, -5.0, 0.0), (0.0, 1.0, 0.0), 4.0, 6.0),
+            ((0.5, 0.0, 5.0), (0.0, 0.0, -1.0), 4.0, 6.0),
+            ((0.5, 0.0, -5.0), (0.0, 0.0, 1.0), 4.0, 6.0),
+            ((0.0, 0.5, 0.0), (0.0, 0.0, 1.0), -1.0, 1.0),
+        ];
+
+        for ((ox, oy, oz), (dx, dy, dz), t1, t2) in cases.iter() {
+            let r = Ray::new(Point3D::new(*ox, *oy, *oz), Vector3D::new(*dx, *dy, *dz));
+
+            assert_eq!(
+                c.local_intersect(&r),
+                vec![Intersection::new(*t1, 0), Intersection::new(*t2, 0)]
+            );
+        }
+    }
+
+    #[test]
+    fn ray_misses_cube() {
+        let c = Cube::new();
+        let cases = [
+            ((-2.0, 0.0, 0.0), (0.2673, 0.5345, 0.8018)),
+            ((0.0, -2.0, 0.0), (0.8018, 0.2673, 0.5345)),
+            ((0.0, 0.0, -2.0), (0.5345, 0.8018, 0.2673)),
+            ((2.0, 0.0, 2.0), (0.0, 0.0, -1.0)),
+            ((0.0, 2.0, 2.0), (0.0, -1.0, 0.0)),
+            ((2.0, 2.0, 0.0), (-1.0, 0.0, 0.0)),
+        ];
+
+        for ((ox, oy, oz), (dx, dy, dz)) in cases.iter() {
+            let r = Ray::new(Point3D::new(*ox, *oy, *oz), Vector3D::new(*dx, *dy, *dz));
+
+            assert_eq!(c.intersect(&r), Interaction::Miss);
+        }
+    }
+
+    #[test]
+    fn normal_on_surface() {
+        let hit = Intersection::new(0.0, 0);
+        let c = Cube::new();
+        let cases = [
+            ((1.0, 0.5, -0.8), (1.0, 0.0, 0.0)),
+            ((-1.0, -0.2, 0.9), (-1.0, 0.0, 0.0)),
+            ((-0.4, 1.0, -0.1), (0.0, 1.0, 0.0)),
+            ((0.3, -1.0, -0.7), (0.0, -1.0, 0.0)),
+            ((-0.6, 0.3, 1.0), (0.0, 0.0, 1.0)),
+            ((0.4, 0.4, -1.0), (0.0, 0.0, -1.0)),
+            ((1.0, 1.0, 1.0), (1.0, 0.0, 0.0)),
+            ((-1.0, -1.0, -1.0), (-1.0, 0.0, 0.0)),
+        ];
+
+        for ((px, py, pz), (nx, ny, nz)) in cases.iter() {
+            assert_eq!(
+                c.local_normal_at(Point3D::new(*px, *py, *pz), &hit),
+                Vector3D::new(*nx, *ny, *nz)
+            );
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use super::{Properties, Shape};
+use crate::bounds::Bounds;
+use crate::descartes::{Point3D, Vector3D};
+use crate::raycasting::{Intersection, Ray};
+use crate::EPSILON;
+
+/// A cylinder of radius 1 around the y axis. It is infinitely long
+/// unless truncated by `minimum` and `maximum`, which are exclusive,
+/// and is open at the ends unless `closed`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cylinder {
+    properties: Properties,
+    minimum: f64,
+    maximum: f64,
+    closed: bool,
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Cylinder {
+            properties: Properties::default(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Cylinder {
+    pub fn new() -> Self {
+        Cylinder::default()
+    }
+
+    pub fn truncated(minimum: f64, maximum: f64, closed: bool) -> Self {
+        Cylinder {
+            minimum,
+            maximum,
+            closed,
+            ..Cylinder::default()
+        }
+    }
+
+    pub fn minimum(&self) -> f64 {
+        self.minimum
+    }
+
+    pub fn set_minimum(&mut self, minimum: f64) {
+        self.minimum = minimum
+    }
+
+    pub fn maximum(&self) -> f64 {
+        self.maximum
+    }
+
+    pub fn set_maximum(&mut self, maximum: f64) {
+        self.maximum = maximum
+    }
+
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn set_closed(&mut self, closed: bool) {
+        self.closed = closed
+    }
+}
+
+/// Whether the ray at `t` is within `radius` of the y axis, i.e. inside
+/// an end cap of that radius.
+pub(super) fn check_cap(ray: &Ray, t: f64, radius: f64) -> bool {
+    let x = ray.origin().x() + t * ray.direction().x();
+    let z = ray.origin().z() + t * ray.direction().z();
+
+    (x.powi(2) + z.powi(2)) <= radius.powi(2) + EPSILON
+}
+
+impl Shape for Cylinder {
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let (origin, direction) = (ray.origin(), ray.direction());
+        let mut xs = Vec::new();
+        let a = direction.x().powi(2) + direction.z().powi(2);
+        // Scaling the cylinder scales the local direction, so whether
+        // it is parallel is judged relative to its length.
+        let length = direction.mag();
+
+        // A ray parallel to the y axis can't hit the walls, but may
+        // still pass through the caps.
+        if a.abs() >= EPSILON * length.powi(2) {
+            let b = 2.0 * origin.x() * direction.x() + 2.0 * origin.z() * direction.z();
+            let c = origin.x().powi(2) + origin.z().powi(2) - 1.0;
+            let disc = b.powi(2) - 4.0 * a * c;
+
+            if disc < 0.0 {
+                return xs;
+            }
+
+            let t0 = (-b - disc.sqrt()) / (2.0 * a);
+            let t1 = (-b + disc.sqrt()) / (2.0 * a);
+            for t in [t0.min(t1), t0.max(t1)].iter() {
+                let y = origin.y() + t * direction.y();
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(*t, self.id()))
+                }
+            }
+        }
+
+        if self.closed && direction.y().abs() >= EPSILON * length {
+            for cap in [self.minimum, self.maximum].iter() {
+                let t = (cap - origin.y()) / direction.y();
+                if check_cap(ray, t, 1.0) {
+                    xs.push(Intersection::new(t, self.id()))
+                }
+            }
+        }
+
+        xs
+    }
+
+    fn local_normal_at(&self, point: Point3D<f64>, _hit: &Intersection) -> Vector3D<f64> {
+        let dist = point.x().powi(2) + point.z().powi(2);
+
+        if dist < 1.0 && point.y() >= self.maximum - EPSILON {
+            Vector3D::new(0.0, 1.0, 0.0)
+        } else if dist < 1.0 && point.y() <= self.minimum + EPSILON {
+            Vector3D::new(0.0, -1.0, 0.0)
+        } else {
+            Vector3D::new(point.x(), 0.0, point.z())
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point3D::new(-1.0, self.minimum, -1.0),
+            Point3D::new(1.0, self.maximum, 1.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::raycasting::Interaction;
+    use crate::transformations::Transformation;
+
+    fn ray(origin: (f64, f64, f64), direction: (f64, f64, f64)) -> Ray {
+        Ray::new(
+            Point3D::new(origin.0, origin.1, origin.2),
+            Vector3D::new(direction.0, direction.1, direction.2).normalize(),
+        )
+    }
+
+    #[test]
+    fn default_cylinder() {
+        let c = Cylinder::new();
+
+        assert_eq!(c.minimum(), f64::NEG_INFINITY);
+        assert_eq!(c.maximum(), f64::INFINITY);
+        assert!(!c.closed());
+    }
+
+    #[test]
+    fn ray_misses_cylinder() {
+        let c = Cylinder::new();
+        let cases = [
+            ((1.0, 0.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, 0.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, 0.0, -5.0), (1.0, 1.0, 1.0)),
+        ];
+
+        for (origin, direction) in cases.iter() {
+            assert!(c.local_intersect(&ray(*origin, *direction)).is_empty());
+        }
+    }
+
+    #[test]
+    fn ray_strikes_cylinder() {
+        let c = Cylinder::new();
+        let cases = [
+            ((1.0, 0.0, -5.0), (0.0, 0.0, 1.0), 5.0, 5.0),
+            ((0.0, 0.0, -5.0), (0.0, 0.0, 1.0), 4.0, 6.0),
+            ((0.5, 0.0, -5.0), (0.1, 1.0, 1.0), 6.80798, 7.08872),
+        ];
+
+        for (origin, direction, t0, t1) in cases.iter() {
+            let xs = c.local_intersect(&ray(*origin, *direction));
+
+            assert_eq!(xs.len(), 2);
+            assert!((xs[0].t() - t0).abs() < EPSILON);
+            assert!((xs[1].t() - t1).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn cylinder_normal() {
+        let hit = Intersection::new(0.0, 0);
+        let c = Cylinder::new();
+        let cases = [
+            ((1.0, 0.0, 0.0), (1.0, 0.0, 0.0)),
+            ((0.0, 5.0, -1.0), (0.0, 0.0, -1.0)),
+            ((0.0, -2.0, 1.0), (0.0, 0.0, 1.0)),
+            ((-1.0, 1.0, 0.0), (-1.0, 0.0, 0.0)),
+        ];
+
+        for ((px, py, pz), (nx, ny, nz)) in cases.iter() {
+            assert_eq!(
+                c.local_normal_at(Point3D::new(*px, *py, *pz), &hit),
+                Vector3D::new(*nx, *ny, *nz)
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_cylinder() {
+        let c = Cylinder::truncated(1.0, 2.0, false);
+        let cases = [
+            ((0.0, 1.5, 0.0), (0.1, 1.0, 0.0), 0),
+            ((0.0, 3.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 0.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 2.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 1.0, -5.0), (0.0, 0.0, 1.0), 0),
+            ((0.0, 1.5, -2.0), (0.0, 0.0, 1.0), 2),
+        ];
+
+        for (origin, direction, count) in cases.iter() {
+            assert_eq!(c.local_intersect(&ray(*origin, *direction)).len(), *count);
+        }
+    }
+
+    #[test]
+    fn capped_cylinder() {
+        let c = Cylinder::truncated(1.0, 2.0, true);
+        let cases = [
+            ((0.0, 3.0, 0.0), (0.0, -1.0, 0.0), 2),
+            ((0.0, 3.0, -2.0), (0.0, -1.0, 2.0), 2),
+            ((0.0, 4.0, -2.0), (0.0, -1.0, 1.0), 2),
+            ((0.0, 0.0, -2.0), (0.0, 1.0, 2.0), 2),
+            ((0.0, -1.0, -2.0), (0.0, 1.0, 1.0), 2),
+        ];
+
+        for (origin, direction, count) in cases.iter() {
+            assert_eq!(c.local_intersect(&ray(*origin, *direction)).len(), *count);
+        }
+    }
+
+    #[test]
+    fn capped_cylinder_normal() {
+        let hit = Intersection::new(0.0, 0);
+        let c = Cylinder::truncated(1.0, 2.0, true);
+        let cases = [
+            ((0.0, 1.0, 0.0), (0.0, -1.0, 0.0)),
+            ((0.5, 1.0, 0.0), (0.0, -1.0, 0.0)),
+            ((0.0, 1.0, 0.5), (0.0, -1.0, 0.0)),
+            ((0.0, 2.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.5, 2.0, 0.0), (0.0, 1.0, 0.0)),
+            ((0.0, 2.0, 0.5), (0.0, 1.0, 0.0)),
+        ];
+
+        for ((px, py, pz), (nx, ny, nz)) in cases.iter() {
+            assert_eq!(
+                c.local_normal_at(Point3D::new(*px, *py, *pz), &hit),
+                Vector3D::new(*nx, *ny, *nz)
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_bounds() {
+        let b = Cylinder::truncated(-5.0, 3.0, true).bounds();
+
+        assert_eq!(b.min(), Point3D::new(-1.0, -5.0, -1.0));
+        assert_eq!(b.max(), Point3D::new(1.0, 3.0, 1.0));
+    }
+
+    #[test]
+    fn scaled_cylinder() {
+        let mut c = Cylinder::new();
+        c.set_transform(Transformation::scaling(500.0, 500.0, 500.0));
+        let r = Ray::new(
+            Point3D::new(0.0, 0.0, -1500.0),
+            Vector3D::new(0.0, 0.0, 1.0),
+        );
+        let xs = match c.intersect(&r) {
+            Interaction::Collision(xs) => xs,
+            Interaction::Miss => panic!("ray missed the cylinder"),
+        };
+
+        assert_eq!(xs.len(), 2);
+        assert!((xs[0].t() - 1000.0).abs() < EPSILON);
+        assert!((xs[1].t() - 2000.0).abs() < EPSILON);
+    }
+}
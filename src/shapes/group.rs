@@ -0,0 +1,80 @@
+use super::{Properties, Shape};
+use crate::bounds::Bounds;
+use crate::descartes::{Point3D, Vector3D};
+use crate::raycasting::{Intersection, Ray};
+
+/// A collection of shapes which are placed in the world together. A
+/// child's transformation is relative to its group, so a group's own
+/// transformation applies to everything in it.
+///
+/// Groups only record the ids of their children; the children live
+/// alongside every other shape in a `World`, which follows the links
+/// when intersecting rays and working out normals.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Group {
+    properties: Properties,
+    children: Vec<usize>,
+}
+
+impl Group {
+    pub fn new() -> Self {
+        Group::default()
+    }
+}
+
+impl Shape for Group {
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    /// A group has no surface of its own; its children are intersected
+    /// separately by the world.
+    fn local_intersect(&self, _ray: &Ray) -> Vec<Intersection> {
+        Vec::new()
+    }
+
+    /// Panics, as a ray can never hit a group itself, only its children.
+    fn local_normal_at(&self, _point: Point3D<f64>, _hit: &Intersection) -> Vector3D<f64> {
+        panic!("groups have no surface, so no normal")
+    }
+
+    /// Groups can't see their children, so this is an empty box.
+    fn bounds(&self) -> Bounds {
+        Bounds::empty()
+    }
+
+    fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<usize>> {
+        Some(&mut self.children)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transformations::Transformation;
+
+    #[test]
+    fn new_group() {
+        let g = Group::new();
+
+        assert_eq!(g.transform(), &Transformation::identity());
+        assert!(g.children().is_empty());
+        assert_eq!(g.parent(), None);
+    }
+
+    #[test]
+    fn empty_group_is_never_hit() {
+        let g = Group::new();
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert!(g.local_intersect(&r).is_empty());
+    }
+}
@@ -0,0 +1,296 @@
+use crate::bounds::Bounds;
+use crate::descartes::{Point3D, Vector3D};
+use crate::lighting::Material;
+use crate::matrix::M4;
+use crate::raycasting::{Interaction, Intersection, Intersections, Ray};
+use crate::transformations::Transformation;
+
+mod cone;
+mod csg;
+mod cube;
+mod cylinder;
+mod group;
+mod plane;
+mod sphere;
+mod triangle;
+
+pub use cone::Cone;
+pub use csg::{Csg, CsgOperation};
+pub use cube::Cube;
+pub use cylinder::Cylinder;
+pub use group::Group;
+pub use plane::Plane;
+pub use sphere::Sphere;
+pub use triangle::{SmoothTriangle, Triangle};
+
+/// The state every shape carries regardless of its geometry: its id
+/// within a scene, the group it belongs to, where it is placed, and
+/// what it is made of.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Properties {
+    id: usize,
+    parent: Option<usize>,
+    transform: Transformation,
+    inverse: M4,
+    material: Material,
+}
+
+impl Default for Properties {
+    fn default() -> Self {
+        Properties {
+            id: 0,
+            parent: None,
+            transform: Transformation::identity(),
+            inverse: M4::identity(),
+            material: Material::default(),
+        }
+    }
+}
+
+impl Properties {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    pub fn transform(&self) -> &Transformation {
+        &self.transform
+    }
+
+    pub fn inverse(&self) -> M4 {
+        self.inverse
+    }
+
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+}
+
+/// A primitive which can be placed in a scene.
+///
+/// Implementors only deal with a canonical shape in object space, i.e.
+/// a unit sphere at the origin; moving rays and normals between world
+/// and object space is handled once by the provided methods.
+pub trait Shape: std::fmt::Debug {
+    fn properties(&self) -> &Properties;
+
+    fn properties_mut(&mut self) -> &mut Properties;
+
+    /// Intersections between the shape and a ray already transformed
+    /// into object space.
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection>;
+
+    /// The normal at a point on the surface, in object space.
+    fn local_normal_at(&self, point: Point3D<f64>, hit: &Intersection) -> Vector3D<f64>;
+
+    /// A box enclosing the shape, in object space.
+    fn bounds(&self) -> Bounds;
+
+    fn id(&self) -> usize {
+        self.properties().id
+    }
+
+    /// Shapes are given their id when they're added to a scene, so
+    /// that it matches their position in it.
+    fn set_id(&mut self, id: usize) {
+        self.properties_mut().id = id
+    }
+
+    /// The id of the group containing this shape, if any.
+    fn parent(&self) -> Option<usize> {
+        self.properties().parent
+    }
+
+    fn set_parent(&mut self, parent: Option<usize>) {
+        self.properties_mut().parent = parent
+    }
+
+    /// The ids of shapes nested within this one. Only groups have any.
+    fn children(&self) -> &[usize] {
+        &[]
+    }
+
+    /// Room for more children, or `None` for shapes which can't have
+    /// any.
+    fn children_mut(&mut self) -> Option<&mut Vec<usize>> {
+        None
+    }
+
+    /// Chooses which of the sorted intersections with this shape's
+    /// children count as hits on the shape itself. `in_first_child`
+    /// says whether an object is, or is nested within, the first child.
+    ///
+    /// Every hit is kept by default, which is right for groups.
+    fn filter_intersections(
+        &self,
+        xs: Intersections,
+        _in_first_child: &dyn Fn(usize) -> bool,
+    ) -> Intersections {
+        xs
+    }
+
+    fn transform(&self) -> &Transformation {
+        &self.properties().transform
+    }
+
+    /// Places the shape in the world. The inverse is computed once
+    /// here, since every intersection and normal needs it.
+    ///
+    /// Panics if the transformation cannot be inverted, e.g. a scaling
+    /// by zero along some axis.
+    fn set_transform(&mut self, transform: Transformation) {
+        let properties = self.properties_mut();
+        properties.inverse = transform
+            .matrix()
+            .inverse()
+            .expect("object transformations must be invertible");
+        properties.transform = transform;
+    }
+
+    fn material(&self) -> &Material {
+        &self.properties().material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.properties_mut().material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.properties_mut().material = material
+    }
+
+    fn intersect(&self, ray: &Ray) -> Interaction {
+        let local_ray = ray.transform(self.properties().inverse);
+        let xs = self.local_intersect(&local_ray);
+        if xs.is_empty() {
+            return Interaction::Miss;
+        }
+        Interaction::Collision(xs)
+    }
+
+    /// The hit is passed through for shapes whose normal depends on
+    /// more than the point, such as smooth triangles.
+    ///
+    /// This only accounts for the shape's own transformation. Shapes
+    /// within a group should go through `World::normal_at` instead.
+    fn normal_at(&self, world_point: Point3D<f64>, hit: &Intersection) -> Vector3D<f64> {
+        let inverse = self.properties().inverse;
+        let local_normal = self.local_normal_at(inverse * world_point, hit);
+        // Normals are transformed by the inverse transpose so they stay
+        // perpendicular to the surface under non-uniform scaling; the
+        // w component this would produce is discarded by the multiply.
+        let world_normal = inverse.transpose() * local_normal;
+        world_normal.normalize()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::EPSILON;
+
+    /// A shape which records nothing but the ray it was handed, for
+    /// checking the shared world-to-object plumbing.
+    #[derive(Debug, Default)]
+    struct TestShape {
+        properties: Properties,
+        saved_ray: std::cell::RefCell<Option<Ray>>,
+    }
+
+    impl Shape for TestShape {
+        fn properties(&self) -> &Properties {
+            &self.properties
+        }
+
+        fn properties_mut(&mut self) -> &mut Properties {
+            &mut self.properties
+        }
+
+        fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+            self.saved_ray.replace(Some(ray.clone()));
+            Vec::new()
+        }
+
+        fn local_normal_at(&self, point: Point3D<f64>, _hit: &Intersection) -> Vector3D<f64> {
+            point - Point3D::new(0.0, 0.0, 0.0)
+        }
+
+        fn bounds(&self) -> Bounds {
+            Bounds::new(Point3D::new(-1.0, -1.0, -1.0), Point3D::new(1.0, 1.0, 1.0))
+        }
+    }
+
+    #[test]
+    fn default_properties() {
+        let s = TestShape::default();
+
+        assert_eq!(s.transform(), &Transformation::identity());
+        assert_eq!(s.material(), &Material::default());
+    }
+
+    #[test]
+    fn assign_material() {
+        let mut s = TestShape::default();
+        let mut m = Material::default();
+        m.set_ambient(1.0);
+        s.set_material(m.clone());
+
+        assert_eq!(s.material(), &m);
+    }
+
+    #[test]
+    fn intersect_scaled_shape() {
+        let mut s = TestShape::default();
+        s.set_transform(Transformation::scaling(2.0, 2.0, 2.0));
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert_eq!(s.intersect(&r), Interaction::Miss);
+        let saved = s.saved_ray.borrow().clone().unwrap();
+        assert_eq!(saved.origin(), Point3D::new(0.0, 0.0, -2.5));
+        assert_eq!(saved.direction(), Vector3D::new(0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn intersect_translated_shape() {
+        let mut s = TestShape::default();
+        s.set_transform(Transformation::translation(5.0, 0.0, 0.0));
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        s.intersect(&r);
+
+        let saved = s.saved_ray.borrow().clone().unwrap();
+        assert_eq!(saved.origin(), Point3D::new(-5.0, 0.0, -5.0));
+        assert_eq!(saved.direction(), Vector3D::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn normal_on_translated_shape() {
+        let hit = Intersection::new(0.0, 0);
+        let mut s = TestShape::default();
+        s.set_transform(Transformation::translation(0.0, 1.0, 0.0));
+        let k = 2.0_f64.sqrt() / 2.0;
+
+        assert_eq!(
+            s.normal_at(Point3D::new(0.0, 1.0 + k, -k), &hit),
+            Vector3D::new(0.0, k, -k)
+        );
+    }
+
+    #[test]
+    fn normal_on_transformed_shape() {
+        use std::f64::consts::PI;
+
+        let hit = Intersection::new(0.0, 0);
+        let mut s = TestShape::default();
+        s.set_transform(
+            Transformation::scaling(1.0, 0.5, 1.0) * Transformation::rotation_z(PI / 5.0),
+        );
+        let k = 2.0_f64.sqrt() / 2.0;
+        let n = s.normal_at(Point3D::new(0.0, k, -k), &hit);
+
+        assert_eq!(n, Vector3D::new(0.0, 0.97014, -0.24254));
+        assert!((n.mag() - 1.0).abs() < EPSILON);
+    }
+}
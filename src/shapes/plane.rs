@@ -0,0 +1,141 @@
+use super::{Properties, Shape};
+use crate::bounds::Bounds;
+use crate::descartes::{Point3D, Vector3D};
+use crate::raycasting::{Intersection, Ray};
+use crate::EPSILON;
+
+/// An infinite plane through the origin, extending along x and z.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Plane {
+    properties: Properties,
+}
+
+impl Plane {
+    pub fn new() -> Self {
+        Plane::default()
+    }
+}
+
+impl Shape for Plane {
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        // A ray parallel to the plane never meets it, and one lying
+        // within it is treated as a miss since the plane has no depth.
+        if ray.direction().y().abs() < EPSILON {
+            return Vec::new();
+        }
+
+        let t = -ray.origin().y() / ray.direction().y();
+        vec![Intersection::new(t, self.id())]
+    }
+
+    /// The normal is the same everywhere on a plane.
+    fn local_normal_at(&self, _point: Point3D<f64>, _hit: &Intersection) -> Vector3D<f64> {
+        Vector3D::new(0.0, 1.0, 0.0)
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point3D::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point3D::new(f64::INFINITY, 0.0, f64::INFINITY),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::raycasting::Interaction;
+    use crate::transformations::Transformation;
+
+    #[test]
+    fn plane_normal_is_constant() {
+        let hit = Intersection::new(0.0, 0);
+        let p = Plane::new();
+        let n = Vector3D::new(0.0, 1.0, 0.0);
+
+        assert_eq!(p.normal_at(Point3D::new(0.0, 0.0, 0.0), &hit), n);
+        assert_eq!(p.normal_at(Point3D::new(10.0, 0.0, -10.0), &hit), n);
+        assert_eq!(p.normal_at(Point3D::new(-5.0, 0.0, 150.0), &hit), n);
+    }
+
+    #[test]
+    fn transformed_plane_normal() {
+        use std::f64::consts::PI;
+
+        let hit = Intersection::new(0.0, 0);
+        let mut p = Plane::new();
+        p.set_transform(Transformation::rotation_z(PI / 2.0));
+
+        assert_eq!(
+            p.normal_at(Point3D::new(0.0, 0.0, 0.0), &hit),
+            Vector3D::new(-1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn parallel_to_plane() {
+        let p = Plane::new();
+        let r = Ray::new(Point3D::new(0.0, 10.0, 0.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert_eq!(p.intersect(&r), Interaction::Miss);
+    }
+
+    #[test]
+    fn coplanar_with_plane() {
+        let p = Plane::new();
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert_eq!(p.intersect(&r), Interaction::Miss);
+    }
+
+    #[test]
+    fn plane_from_above() {
+        let p = Plane::new();
+        let r = Ray::new(Point3D::new(0.0, 1.0, 0.0), Vector3D::new(0.0, -1.0, 0.0));
+
+        assert_eq!(
+            p.intersect(&r),
+            Interaction::Collision(vec![Intersection::new(1.0, 0)])
+        );
+    }
+
+    #[test]
+    fn plane_from_below() {
+        let p = Plane::new();
+        let r = Ray::new(Point3D::new(0.0, -1.0, 0.0), Vector3D::new(0.0, 1.0, 0.0));
+
+        assert_eq!(
+            p.intersect(&r),
+            Interaction::Collision(vec![Intersection::new(1.0, 0)])
+        );
+    }
+
+    #[test]
+    fn translated_plane() {
+        let mut p = Plane::new();
+        p.set_transform(Transformation::translation(0.0, -2.0, 0.0));
+        let r = Ray::new(Point3D::new(0.0, 1.0, 0.0), Vector3D::new(0.0, -1.0, 0.0));
+
+        assert_eq!(
+            p.intersect(&r),
+            Interaction::Collision(vec![Intersection::new(3.0, 0)])
+        );
+    }
+
+    #[test]
+    fn plane_bounds() {
+        let b = Plane::new().bounds();
+
+        assert_eq!(b.min().y(), 0.0);
+        assert_eq!(b.max().y(), 0.0);
+        assert!(b.min().x().is_infinite() && b.max().z().is_infinite());
+    }
+}
@@ -0,0 +1,125 @@
+use super::{Properties, Shape};
+use crate::bounds::Bounds;
+use crate::descartes::{Point3D, Vector3D};
+use crate::raycasting::{Intersection, Ray};
+
+/// A unit sphere centred on the origin.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Sphere {
+    properties: Properties,
+}
+
+impl Sphere {
+    pub fn new() -> Self {
+        Sphere::default()
+    }
+}
+
+impl Shape for Sphere {
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        let ro = ray.origin() - Point3D::new(0.0, 0.0, 0.0);
+        let a = ray.direction().dot(ray.direction());
+        let b = 2.0 * ray.direction().dot(ro);
+        let c = ro.dot(ro) - 1.0;
+        let d = b.powi(2) - (4.0 * a * c);
+
+        if d < 0.0 {
+            return Vec::new();
+        }
+
+        vec![
+            Intersection::new((-b - d.sqrt()) / (2.0 * a), self.id()),
+            Intersection::new((-b + d.sqrt()) / (2.0 * a), self.id()),
+        ]
+    }
+
+    fn local_normal_at(&self, point: Point3D<f64>, _hit: &Intersection) -> Vector3D<f64> {
+        point - Point3D::new(0.0, 0.0, 0.0)
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(Point3D::new(-1.0, -1.0, -1.0), Point3D::new(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transformations::Transformation;
+
+    #[test]
+    fn normal_on_axes() {
+        let hit = Intersection::new(0.0, 0);
+        let s = Sphere::new();
+
+        assert_eq!(
+            s.normal_at(Point3D::new(1.0, 0.0, 0.0), &hit),
+            Vector3D::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            s.normal_at(Point3D::new(0.0, 1.0, 0.0), &hit),
+            Vector3D::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            s.normal_at(Point3D::new(0.0, 0.0, 1.0), &hit),
+            Vector3D::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn normal_is_normalized() {
+        let hit = Intersection::new(0.0, 0);
+        let s = Sphere::new();
+        let k = 3.0_f64.sqrt() / 3.0;
+        let n = s.normal_at(Point3D::new(k, k, k), &hit);
+
+        assert_eq!(n, Vector3D::new(k, k, k));
+        assert_eq!(n, n.normalize());
+    }
+
+    #[test]
+    fn normal_on_translated_sphere() {
+        let hit = Intersection::new(0.0, 0);
+        let mut s = Sphere::new();
+        s.set_transform(Transformation::translation(0.0, 1.0, 0.0));
+        let k = 2.0_f64.sqrt() / 2.0;
+
+        assert_eq!(
+            s.normal_at(Point3D::new(0.0, 1.0 + k, -k), &hit),
+            Vector3D::new(0.0, k, -k)
+        );
+    }
+
+    #[test]
+    fn normal_on_transformed_sphere() {
+        use std::f64::consts::PI;
+
+        let hit = Intersection::new(0.0, 0);
+        let mut s = Sphere::new();
+        s.set_transform(
+            Transformation::scaling(1.0, 0.5, 1.0) * Transformation::rotation_z(PI / 5.0),
+        );
+        let k = 2.0_f64.sqrt() / 2.0;
+
+        assert_eq!(
+            s.normal_at(Point3D::new(0.0, k, -k), &hit),
+            Vector3D::new(0.0, 0.97014, -0.24254)
+        );
+    }
+
+    #[test]
+    fn sphere_bounds() {
+        let b = Sphere::new().bounds();
+
+        assert_eq!(b.min(), Point3D::new(-1.0, -1.0, -1.0));
+        assert_eq!(b.max(), Point3D::new(1.0, 1.0, 1.0));
+    }
+}
@@ -0,0 +1,331 @@
+use super::{Properties, Shape};
+use crate::bounds::Bounds;
+use crate::descartes::{Point3D, Vector3D};
+use crate::raycasting::{Intersection, Ray};
+use crate::EPSILON;
+
+/// A flat triangle between three points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triangle {
+    properties: Properties,
+    p1: Point3D<f64>,
+    p2: Point3D<f64>,
+    p3: Point3D<f64>,
+    e1: Vector3D<f64>,
+    e2: Vector3D<f64>,
+    normal: Vector3D<f64>,
+}
+
+impl Triangle {
+    pub fn new(p1: Point3D<f64>, p2: Point3D<f64>, p3: Point3D<f64>) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+
+        Triangle {
+            properties: Properties::default(),
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(e1).normalize(),
+        }
+    }
+
+    pub fn p1(&self) -> Point3D<f64> {
+        self.p1
+    }
+
+    pub fn p2(&self) -> Point3D<f64> {
+        self.p2
+    }
+
+    pub fn p3(&self) -> Point3D<f64> {
+        self.p3
+    }
+
+    pub fn e1(&self) -> Vector3D<f64> {
+        self.e1
+    }
+
+    pub fn e2(&self) -> Vector3D<f64> {
+        self.e2
+    }
+
+    pub fn normal(&self) -> Vector3D<f64> {
+        self.normal
+    }
+
+    /// Möller–Trumbore intersection, returning `t` along with the
+    /// barycentric `u` and `v` of the hit.
+    fn intersect_uv(&self, ray: &Ray) -> Option<(f64, f64, f64)> {
+        let dir_cross_e2 = ray.direction().cross(self.e2);
+        let det = self.e1.dot(dir_cross_e2);
+        // The ray is parallel to the triangle's plane. `det` scales with
+        // the lengths of both edges and the direction, so the cutoff
+        // does too, or else small triangles would never be hit.
+        if det.abs() <= EPSILON * self.e1.mag() * self.e2.mag() * ray.direction().mag() {
+            return None;
+        }
+
+        let f = 1.0 / det;
+        let p1_to_origin = ray.origin() - self.p1;
+        let u = f * p1_to_origin.dot(dir_cross_e2);
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let origin_cross_e1 = p1_to_origin.cross(self.e1);
+        let v = f * ray.direction().dot(origin_cross_e1);
+        if v < 0.0 || (u + v) > 1.0 {
+            return None;
+        }
+
+        Some((f * self.e2.dot(origin_cross_e1), u, v))
+    }
+
+    fn triangle_bounds(&self) -> Bounds {
+        Bounds::from_points(&[self.p1, self.p2, self.p3])
+    }
+}
+
+impl Shape for Triangle {
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        match self.intersect_uv(ray) {
+            Some((t, u, v)) => vec![Intersection::with_uv(t, self.id(), u, v)],
+            None => Vec::new(),
+        }
+    }
+
+    fn local_normal_at(&self, _point: Point3D<f64>, _hit: &Intersection) -> Vector3D<f64> {
+        self.normal
+    }
+
+    fn bounds(&self) -> Bounds {
+        self.triangle_bounds()
+    }
+}
+
+/// A triangle with a normal at each vertex. Normals are interpolated
+/// across the face, which hides the facets of a coarse mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothTriangle {
+    triangle: Triangle,
+    n1: Vector3D<f64>,
+    n2: Vector3D<f64>,
+    n3: Vector3D<f64>,
+}
+
+impl SmoothTriangle {
+    pub fn new(
+        p1: Point3D<f64>,
+        p2: Point3D<f64>,
+        p3: Point3D<f64>,
+        n1: Vector3D<f64>,
+        n2: Vector3D<f64>,
+        n3: Vector3D<f64>,
+    ) -> Self {
+        SmoothTriangle {
+            triangle: Triangle::new(p1, p2, p3),
+            n1,
+            n2,
+            n3,
+        }
+    }
+
+    pub fn p1(&self) -> Point3D<f64> {
+        self.triangle.p1
+    }
+
+    pub fn p2(&self) -> Point3D<f64> {
+        self.triangle.p2
+    }
+
+    pub fn p3(&self) -> Point3D<f64> {
+        self.triangle.p3
+    }
+
+    pub fn n1(&self) -> Vector3D<f64> {
+        self.n1
+    }
+
+    pub fn n2(&self) -> Vector3D<f64> {
+        self.n2
+    }
+
+    pub fn n3(&self) -> Vector3D<f64> {
+        self.n3
+    }
+}
+
+impl Shape for SmoothTriangle {
+    fn properties(&self) -> &Properties {
+        &self.triangle.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Properties {
+        &mut self.triangle.properties
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection> {
+        self.triangle.local_intersect(ray)
+    }
+
+    /// Panics if `hit` did not come from this triangle, as it will have
+    /// no u/v to interpolate with.
+    fn local_normal_at(&self, _point: Point3D<f64>, hit: &Intersection) -> Vector3D<f64> {
+        let (u, v) = hit
+            .uv()
+            .expect("smooth triangle normals need the u/v of the hit");
+
+        self.n2 * u + self.n3 * v + self.n1 * (1.0 - u - v)
+    }
+
+    fn bounds(&self) -> Bounds {
+        self.triangle.triangle_bounds()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::raycasting::Interaction;
+
+    fn triangle() -> Triangle {
+        Triangle::new(
+            Point3D::new(0.0, 1.0, 0.0),
+            Point3D::new(-1.0, 0.0, 0.0),
+            Point3D::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    fn smooth_triangle() -> SmoothTriangle {
+        SmoothTriangle::new(
+            Point3D::new(0.0, 1.0, 0.0),
+            Point3D::new(-1.0, 0.0, 0.0),
+            Point3D::new(1.0, 0.0, 0.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+            Vector3D::new(-1.0, 0.0, 0.0),
+            Vector3D::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn construct_triangle() {
+        let t = triangle();
+
+        assert_eq!(t.e1(), Vector3D::new(-1.0, -1.0, 0.0));
+        assert_eq!(t.e2(), Vector3D::new(1.0, -1.0, 0.0));
+        assert_eq!(t.normal(), Vector3D::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn triangle_normal_is_constant() {
+        let t = triangle();
+        let hit = Intersection::new(0.0, 0);
+
+        assert_eq!(
+            t.local_normal_at(Point3D::new(0.0, 0.5, 0.0), &hit),
+            t.normal()
+        );
+        assert_eq!(
+            t.local_normal_at(Point3D::new(-0.5, 0.75, 0.0), &hit),
+            t.normal()
+        );
+        assert_eq!(
+            t.local_normal_at(Point3D::new(0.5, 0.25, 0.0), &hit),
+            t.normal()
+        );
+    }
+
+    #[test]
+    fn ray_parallel_to_triangle() {
+        let r = Ray::new(Point3D::new(0.0, -1.0, -2.0), Vector3D::new(0.0, 1.0, 0.0));
+
+        assert!(triangle().local_intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn ray_misses_edges() {
+        let t = triangle();
+        for origin in [
+            Point3D::new(1.0, 1.0, -2.0),
+            Point3D::new(-1.0, 1.0, -2.0),
+            Point3D::new(0.0, -1.0, -2.0),
+        ]
+        .iter()
+        {
+            let r = Ray::new(*origin, Vector3D::new(0.0, 0.0, 1.0));
+
+            assert_eq!(t.intersect(&r), Interaction::Miss);
+        }
+    }
+
+    #[test]
+    fn ray_strikes_triangle() {
+        let r = Ray::new(Point3D::new(0.0, 0.5, -2.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = triangle().local_intersect(&r);
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t(), 2.0);
+    }
+
+    #[test]
+    fn ray_strikes_tiny_triangle() {
+        let t = Triangle::new(
+            Point3D::new(0.0, 0.001, 0.0),
+            Point3D::new(-0.001, 0.0, 0.0),
+            Point3D::new(0.001, 0.0, 0.0),
+        );
+        let r = Ray::new(
+            Point3D::new(0.0, 0.0005, -2.0),
+            Vector3D::new(0.0, 0.0, 1.0),
+        );
+        let xs = t.local_intersect(&r);
+
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t(), 2.0);
+    }
+
+    #[test]
+    fn intersection_records_uv() {
+        let r = Ray::new(Point3D::new(-0.2, 0.3, -2.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = smooth_triangle().local_intersect(&r);
+        let (u, v) = xs[0].uv().unwrap();
+
+        assert!((u - 0.45).abs() < EPSILON);
+        assert!((v - 0.25).abs() < EPSILON);
+    }
+
+    #[test]
+    fn smooth_normal_is_interpolated() {
+        let tri = smooth_triangle();
+        let hit = Intersection::with_uv(1.0, 0, 0.45, 0.25);
+
+        assert_eq!(
+            tri.normal_at(Point3D::new(0.0, 0.0, 0.0), &hit),
+            Vector3D::new(-0.5547, 0.83205, 0.0)
+        );
+    }
+
+    #[test]
+    fn triangle_bounds() {
+        let t = Triangle::new(
+            Point3D::new(-3.0, 7.0, 2.0),
+            Point3D::new(6.0, 2.0, -4.0),
+            Point3D::new(2.0, -1.0, -1.0),
+        );
+        let b = t.bounds();
+
+        assert_eq!(b.min(), Point3D::new(-3.0, -1.0, -4.0));
+        assert_eq!(b.max(), Point3D::new(6.0, 7.0, 2.0));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::descartes::{Point3D, Vector3D};
 use crate::matrix::M4;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Transformation {
     Translation(Box<Transformation>),
     Rotation(Box<Transformation>),
@@ -23,7 +23,22 @@ impl Transformation {
                 mtx.inverse().map(|m| Transformation::Scaling(Box::new(m)))
             }
             Transformation::Shear(mtx) => mtx.inverse().map(|m| Transformation::Shear(Box::new(m))),
-            Transformation::Matrix(mtx) => mtx.inverse().map(|m| Transformation::Matrix(m)),
+            Transformation::Matrix(mtx) => mtx.inverse().map(Transformation::Matrix),
+        }
+    }
+
+    pub fn identity() -> Transformation {
+        Transformation::Matrix(M4::identity())
+    }
+
+    /// Collapses the transformation down to the matrix it represents.
+    pub fn matrix(&self) -> M4 {
+        match self {
+            Transformation::Translation(mtx)
+            | Transformation::Rotation(mtx)
+            | Transformation::Scaling(mtx)
+            | Transformation::Shear(mtx) => mtx.matrix(),
+            Transformation::Matrix(mtx) => *mtx,
         }
     }
 
@@ -52,6 +67,26 @@ impl Transformation {
             xy, xz, yx, yz, zx, zy,
         ))))
     }
+
+    /// Orients the world relative to an eye at `from`, looking towards
+    /// `to`, with `up` roughly indicating which way is up.
+    pub fn view_transform(from: Point3D<f64>, to: Point3D<f64>, up: Vector3D<f64>) -> Self {
+        let forward = (to - from).normalize();
+        let left = forward.cross(up.normalize());
+        // Recomputing up from the other two means the caller's `up`
+        // need not be exactly perpendicular to the line of sight.
+        let true_up = left.cross(forward);
+
+        let mut orientation = M4::identity();
+        for (c, v) in [left, true_up, -forward].iter().enumerate() {
+            orientation.write_idx(c, 0, v.x());
+            orientation.write_idx(c, 1, v.y());
+            orientation.write_idx(c, 2, v.z());
+        }
+
+        Transformation::Matrix(orientation)
+            * Transformation::translation(-from.x(), -from.y(), -from.z())
+    }
 }
 
 impl std::ops::Mul<Point3D<f64>> for Transformation {
@@ -126,6 +161,23 @@ mod test {
         assert_eq!(t.inverse().unwrap() * p, Point3D::new(-8.0, 7.0, 3.0))
     }
 
+    #[test]
+    fn identity_leaves_point() {
+        let p = Point3D::new(-3.0, 4.0, 5.0);
+
+        assert_eq!(Transformation::identity() * p, p)
+    }
+
+    #[test]
+    fn collapse_to_matrix() {
+        let t = Transformation::scaling(2.0, 3.0, 4.0) * Transformation::translation(1.0, 0.0, 0.0);
+
+        assert_eq!(
+            t.matrix(),
+            M4::scaling(2.0, 3.0, 4.0) * M4::translation(1.0, 0.0, 0.0)
+        )
+    }
+
     #[test]
     fn translate_vec() {
         let t = Transformation::translation(5.0, -3.0, 2.0);
@@ -189,11 +241,11 @@ mod test {
         let quarter_turn = Transformation::rotation_x(PI / 2.0);
 
         assert_eq!(
-            eighth_turn * p.clone(),
+            eighth_turn * p,
             Point3D::new(0.0, 2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0)
         );
 
-        assert_eq!(quarter_turn * p.clone(), Point3D::new(0.0, 0.0, 1.0))
+        assert_eq!(quarter_turn * p, Point3D::new(0.0, 0.0, 1.0))
     }
 
     #[test]
@@ -202,7 +254,7 @@ mod test {
         let eighth_turn = Transformation::rotation_x(PI / 4.0).inverse().unwrap();
 
         assert_eq!(
-            eighth_turn * p.clone(),
+            eighth_turn * p,
             Point3D::new(0.0, 2.0_f64.sqrt() / 2.0, -(2.0_f64.sqrt() / 2.0))
         );
     }
@@ -214,11 +266,11 @@ mod test {
         let quarter_turn = Transformation::rotation_y(PI / 2.0);
 
         assert_eq!(
-            eighth_turn * p.clone(),
+            eighth_turn * p,
             Point3D::new(2.0_f64.sqrt() / 2.0, 0.0, 2.0_f64.sqrt() / 2.0)
         );
 
-        assert_eq!(quarter_turn * p.clone(), Point3D::new(1.0, 0.0, 0.0))
+        assert_eq!(quarter_turn * p, Point3D::new(1.0, 0.0, 0.0))
     }
 
     #[test]
@@ -228,11 +280,11 @@ mod test {
         let quarter_turn = Transformation::rotation_z(PI / 2.0);
 
         assert_eq!(
-            eighth_turn * p.clone(),
+            eighth_turn * p,
             Point3D::new(-2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0, 0.0)
         );
 
-        assert_eq!(quarter_turn * p.clone(), Point3D::new(-1.0, 0.0, 0.0))
+        assert_eq!(quarter_turn * p, Point3D::new(-1.0, 0.0, 0.0))
     }
 
     #[test]
@@ -283,6 +335,64 @@ mod test {
         assert_eq!(shear * point, Point3D::new(2.0, 3.0, 7.0))
     }
 
+    #[test]
+    fn default_view() {
+        let t = Transformation::view_transform(
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(0.0, 0.0, -1.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        );
+
+        assert_eq!(t.matrix(), M4::identity())
+    }
+
+    #[test]
+    fn view_positive_z() {
+        let t = Transformation::view_transform(
+            Point3D::new(0.0, 0.0, 0.0),
+            Point3D::new(0.0, 0.0, 1.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        );
+
+        assert_eq!(t.matrix(), M4::scaling(-1.0, 1.0, -1.0))
+    }
+
+    #[test]
+    fn view_moves_world() {
+        let t = Transformation::view_transform(
+            Point3D::new(0.0, 0.0, 8.0),
+            Point3D::new(0.0, 0.0, 0.0),
+            Vector3D::new(0.0, 1.0, 0.0),
+        );
+
+        assert_eq!(t.matrix(), M4::translation(0.0, 0.0, -8.0))
+    }
+
+    #[test]
+    fn arbitrary_view() {
+        let t = Transformation::view_transform(
+            Point3D::new(1.0, 3.0, 2.0),
+            Point3D::new(4.0, -2.0, 8.0),
+            Vector3D::new(1.0, 1.0, 0.0),
+        );
+        let mut expected = M4::new();
+        for (r, row) in [
+            [-0.50709, 0.50709, 0.67612, -2.36643],
+            [0.76772, 0.60609, 0.12122, -2.82843],
+            [-0.35857, 0.59761, -0.71714, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+        .iter()
+        .enumerate()
+        {
+            for (c, v) in row.iter().enumerate() {
+                expected.write_idx(r, c, *v)
+            }
+        }
+
+        assert_eq!(t.matrix(), expected)
+    }
+
     #[test]
     fn sequential_transformations() {
         let p = Point3D::new(1.0, 0.0, 1.0);
@@ -0,0 +1,1324 @@
+use crate::bounds::Bounds;
+use crate::bvh::Bvh;
+use crate::colour::Colour;
+use crate::descartes::{Point3D, Vector3D};
+use crate::lighting::{lighting, PointLight};
+use crate::raycasting::{Intersection, Intersections, Ray, Scene};
+use crate::shapes::{Cube, Group, Plane, Shape, Sphere};
+use crate::transformations::Transformation;
+use crate::EPSILON;
+use std::sync::OnceLock;
+
+/// How many times a ray may bounce before it is abandoned, unless told
+/// otherwise. Two facing mirrors would otherwise recurse forever.
+pub const DEFAULT_DEPTH: usize = 5;
+
+/// Everything needed to shade a single intersection, worked out once
+/// up front.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Computations {
+    t: f64,
+    object: usize,
+    point: Point3D<f64>,
+    over_point: Point3D<f64>,
+    eyev: Vector3D<f64>,
+    normalv: Vector3D<f64>,
+    reflectv: Vector3D<f64>,
+    inside: bool,
+    under_point: Point3D<f64>,
+    n1: f64,
+    n2: f64,
+}
+
+impl Computations {
+    pub fn t(&self) -> f64 {
+        self.t
+    }
+
+    pub fn object(&self) -> usize {
+        self.object
+    }
+
+    pub fn point(&self) -> Point3D<f64> {
+        self.point
+    }
+
+    /// The hit point nudged slightly along the normal, for spawning
+    /// rays that must not hit the surface they start on.
+    pub fn over_point(&self) -> Point3D<f64> {
+        self.over_point
+    }
+
+    pub fn eyev(&self) -> Vector3D<f64> {
+        self.eyev
+    }
+
+    pub fn normalv(&self) -> Vector3D<f64> {
+        self.normalv
+    }
+
+    /// The direction of the eye ray after bouncing off the surface.
+    pub fn reflectv(&self) -> Vector3D<f64> {
+        self.reflectv
+    }
+
+    pub fn inside(&self) -> bool {
+        self.inside
+    }
+
+    /// The hit point nudged slightly below the surface, where refracted
+    /// rays start.
+    pub fn under_point(&self) -> Point3D<f64> {
+        self.under_point
+    }
+
+    /// The refractive index of the material the ray is leaving.
+    pub fn n1(&self) -> f64 {
+        self.n1
+    }
+
+    /// The refractive index of the material the ray is entering.
+    pub fn n2(&self) -> f64 {
+        self.n2
+    }
+
+    /// Schlick's approximation of the Fresnel effect: the fraction of
+    /// light reflected rather than refracted at the hit.
+    pub fn schlick(&self) -> f64 {
+        let mut cos = self.eyev.dot(self.normalv);
+
+        // Total internal reflection is only possible leaving a denser
+        // material, and reflects everything.
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n.powi(2) * (1.0 - cos.powi(2));
+            if sin2_t > 1.0 {
+                return 1.0;
+            }
+            cos = (1.0 - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct World {
+    objects: Vec<Box<dyn Shape>>,
+    lights: Vec<PointLight>,
+    /// Built on first use, and thrown away whenever objects are added,
+    /// moved between groups or transformed.
+    hierarchies: OnceLock<Hierarchies>,
+}
+
+/// Bounding volume hierarchies over the top level objects, and over the
+/// children of each object that has any, in that object's space.
+#[derive(Debug)]
+struct Hierarchies {
+    top: Bvh,
+    children: Vec<Option<Bvh>>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        World {
+            objects: Vec::new(),
+            lights: Vec::new(),
+            hierarchies: OnceLock::new(),
+        }
+    }
+
+    pub fn add<S: Shape + 'static>(&mut self, shape: S) -> &mut dyn Shape {
+        self.add_boxed(Box::new(shape))
+    }
+
+    /// Shapes which are created with children, like `Csg`, become
+    /// their parent here.
+    ///
+    /// Panics if any of those children already has a parent.
+    pub fn add_boxed(&mut self, mut shape: Box<dyn Shape>) -> &mut dyn Shape {
+        self.hierarchies.take();
+        let new_id = self.objects.len();
+        shape.set_id(new_id);
+        for child in shape.children() {
+            let child = &mut self.objects[*child];
+            assert!(
+                child.parent().is_none(),
+                "object {} is already in a group",
+                child.id()
+            );
+            child.set_parent(Some(new_id));
+        }
+        self.objects.push(shape);
+        &mut *self.objects[new_id]
+    }
+
+    pub fn sphere(&mut self) -> &mut dyn Shape {
+        self.add(Sphere::new())
+    }
+
+    pub fn plane(&mut self) -> &mut dyn Shape {
+        self.add(Plane::new())
+    }
+
+    pub fn cube(&mut self) -> &mut dyn Shape {
+        self.add(Cube::new())
+    }
+
+    pub fn group(&mut self) -> &mut dyn Shape {
+        self.add(Group::new())
+    }
+
+    /// Moves the object `child` into the group `group`, after which it
+    /// is placed relative to the group rather than the world.
+    ///
+    /// Panics if `group` isn't a group, if `child` already has a
+    /// parent, or if the move would make a group contain itself.
+    pub fn add_child(&mut self, group: usize, child: usize) {
+        assert!(
+            self.object(child).parent().is_none(),
+            "object {} is already in a group",
+            child
+        );
+        let mut ancestor = Some(group);
+        while let Some(id) = ancestor {
+            assert_ne!(id, child, "a group cannot contain itself");
+            ancestor = self.object(id).parent();
+        }
+
+        self.hierarchies.take();
+        self.objects[group]
+            .children_mut()
+            .expect("only groups can have children")
+            .push(child);
+        self.objects[child].set_parent(Some(group));
+    }
+
+    /// Places the object `id`, and everything nested in it, anew. This
+    /// is how objects are moved once they are in the world, such as a
+    /// group built from a file.
+    ///
+    /// Panics if `transform` isn't invertible.
+    pub fn set_transform(&mut self, id: usize, transform: Transformation) {
+        self.hierarchies.take();
+        self.objects[id].set_transform(transform);
+    }
+
+    pub fn add_light(&mut self, light: PointLight) {
+        self.lights.push(light)
+    }
+
+    pub fn object(&self, id: usize) -> &dyn Shape {
+        &*self.objects[id]
+    }
+
+    pub fn objects(&self) -> &[Box<dyn Shape>] {
+        &self.objects
+    }
+
+    pub fn lights(&self) -> &[PointLight] {
+        &self.lights
+    }
+
+    /// A box around the object `id` and everything nested in it, in
+    /// the space of its parent, or the world if it has none.
+    pub fn bounds(&self, id: usize) -> Bounds {
+        let object = self.object(id);
+        let local = object
+            .children()
+            .iter()
+            .fold(object.bounds(), |acc, child| {
+                acc.union(&self.bounds(*child))
+            });
+        local.transform(object.transform().matrix())
+    }
+
+    fn hierarchies(&self) -> &Hierarchies {
+        self.hierarchies.get_or_init(|| Hierarchies {
+            top: self.hierarchy_over(
+                self.objects
+                    .iter()
+                    .filter(|o| o.parent().is_none())
+                    .map(|o| o.id()),
+            ),
+            children: self
+                .objects
+                .iter()
+                .map(|o| match o.children() {
+                    [] => None,
+                    children => Some(self.hierarchy_over(children.iter().copied())),
+                })
+                .collect(),
+        })
+    }
+
+    fn hierarchy_over<I: Iterator<Item = usize>>(&self, ids: I) -> Bvh {
+        Bvh::build(&ids.map(|id| (id, self.bounds(id))).collect::<Vec<_>>())
+    }
+
+    /// Intersects the ray with every object it might hit, skipping
+    /// those whose bounding boxes it misses.
+    pub fn intersect(&self, ray: &Ray) -> Intersections {
+        let hierarchies = self.hierarchies();
+        let mut xs = Intersections::new();
+        hierarchies.top.candidates(ray, |id| {
+            self.intersect_object(id, ray, &mut xs, Some(hierarchies))
+        });
+        xs
+    }
+
+    /// Intersects the ray with every object in turn. This gives the
+    /// same result as `intersect`, only slower, so is mostly useful for
+    /// checking and benchmarking it.
+    pub fn intersect_exhaustive(&self, ray: &Ray) -> Intersections {
+        let mut xs = Intersections::new();
+        for object in self.objects.iter().filter(|o| o.parent().is_none()) {
+            self.intersect_object(object.id(), ray, &mut xs, None);
+        }
+        xs
+    }
+
+    /// Intersects `ray`, given in the space of the object's parent,
+    /// with the object and everything nested inside it. Without any
+    /// hierarchies, every child is tried.
+    fn intersect_object(
+        &self,
+        id: usize,
+        ray: &Ray,
+        xs: &mut Intersections,
+        hierarchies: Option<&Hierarchies>,
+    ) {
+        let object = self.object(id);
+        let local_ray = ray.transform(object.properties().inverse());
+        xs.merge(object.local_intersect(&local_ray).into());
+
+        if let Some(first) = object.children().first() {
+            let mut child_xs = Intersections::new();
+            let mut visit =
+                |child| self.intersect_object(child, &local_ray, &mut child_xs, hierarchies);
+            match hierarchies.and_then(|h| h.children[id].as_ref()) {
+                Some(bvh) => bvh.candidates(&local_ray, visit),
+                None => object.children().iter().for_each(|c| visit(*c)),
+            }
+            xs.merge(object.filter_intersections(child_xs, &|hit| self.includes(*first, hit)));
+        }
+    }
+
+    /// Whether `object` is `container` itself or is nested somewhere
+    /// within it.
+    pub fn includes(&self, container: usize, object: usize) -> bool {
+        let mut current = Some(object);
+        while let Some(id) = current {
+            if id == container {
+                return true;
+            }
+            current = self.object(id).parent();
+        }
+        false
+    }
+
+    /// Converts a point from world space to the object space of `id`,
+    /// passing through the space of each group it is nested in.
+    pub fn world_to_object(&self, id: usize, point: Point3D<f64>) -> Point3D<f64> {
+        let object = self.object(id);
+        let point = match object.parent() {
+            Some(parent) => self.world_to_object(parent, point),
+            None => point,
+        };
+        object.properties().inverse() * point
+    }
+
+    /// Converts a normal from the object space of `id` to world space,
+    /// passing through the space of each group it is nested in.
+    pub fn normal_to_world(&self, id: usize, normal: Vector3D<f64>) -> Vector3D<f64> {
+        let object = self.object(id);
+        // As in `Shape::normal_at`, the inverse transpose keeps the
+        // normal perpendicular to the surface.
+        let normal = (object.properties().inverse().transpose() * normal).normalize();
+        match object.parent() {
+            Some(parent) => self.normal_to_world(parent, normal),
+            None => normal,
+        }
+    }
+
+    /// The world space normal of the object `id` at `point`, taking
+    /// into account every group it is nested in.
+    pub fn normal_at(&self, id: usize, point: Point3D<f64>, hit: &Intersection) -> Vector3D<f64> {
+        let local_point = self.world_to_object(id, point);
+        let local_normal = self.object(id).local_normal_at(local_point, hit);
+        self.normal_to_world(id, local_normal)
+    }
+
+    /// `xs` are all the intersections along the ray, including `hit`,
+    /// which are needed to tell which objects the hit point lies in.
+    pub fn prepare_computations(
+        &self,
+        hit: &Intersection,
+        ray: &Ray,
+        xs: &Intersections,
+    ) -> Computations {
+        let point = ray.position(hit.t());
+        let eyev = -ray.direction();
+        let mut normalv = self.normal_at(hit.object(), point, hit);
+
+        // When the eye is inside the object the surface normal points
+        // away from it, so flip it to get sensible shading.
+        let inside = normalv.dot(eyev) < 0.0;
+        if inside {
+            normalv = -normalv;
+        }
+        let (n1, n2) = self.refractive_indices(hit, xs);
+
+        Computations {
+            t: hit.t(),
+            object: hit.object(),
+            point,
+            over_point: point + normalv * EPSILON,
+            eyev,
+            normalv,
+            reflectv: ray.direction().reflect(normalv),
+            inside,
+            under_point: point - normalv * EPSILON,
+            n1,
+            n2,
+        }
+    }
+
+    /// The refractive indices either side of the surface at `hit`. The
+    /// objects the ray is inside are tracked along the way, since the
+    /// one it leaves by may not be the last one it entered.
+    fn refractive_indices(&self, hit: &Intersection, xs: &Intersections) -> (f64, f64) {
+        let index_of_last = |containers: &[usize]| match containers.last() {
+            Some(id) => self.object(*id).material().refractive_index(),
+            None => 1.0,
+        };
+        let mut containers: Vec<usize> = Vec::new();
+
+        for i in xs.iter() {
+            let n1 = index_of_last(&containers);
+            match containers.iter().position(|id| *id == i.object()) {
+                Some(idx) => {
+                    containers.remove(idx);
+                }
+                None => containers.push(i.object()),
+            }
+            if i == hit {
+                return (n1, index_of_last(&containers));
+            }
+        }
+
+        (1.0, 1.0)
+    }
+
+    /// The colour at an intersection. `remaining` is how many more
+    /// times rays may bounce off reflective surfaces from here.
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize) -> Colour {
+        let material = self.object(comps.object).material();
+        let object_point = self.world_to_object(comps.object, comps.over_point);
+        let surface = self.lights.iter().fold(Colour::black(), |acc, light| {
+            let shadowed = self.is_shadowed(light, comps.over_point);
+            acc + lighting(
+                material,
+                light,
+                comps.over_point,
+                object_point,
+                comps.eyev,
+                comps.normalv,
+                shadowed,
+            )
+        });
+
+        let reflected = self.reflected_colour(comps, remaining);
+        let refracted = self.refracted_colour(comps, remaining);
+
+        if material.reflective() > 0.0 && material.transparency() > 0.0 {
+            let reflectance = comps.schlick();
+            return surface + reflected * reflectance + refracted * (1.0 - reflectance);
+        }
+        surface + reflected + refracted
+    }
+
+    /// The colour seen in a reflective surface, which is black once
+    /// there are no bounces remaining.
+    pub fn reflected_colour(&self, comps: &Computations, remaining: usize) -> Colour {
+        let reflective = self.object(comps.object).material().reflective();
+        if reflective == 0.0 || remaining == 0 {
+            return Colour::black();
+        }
+
+        let reflect_ray = Ray::new(comps.over_point, comps.reflectv);
+        self.colour_at(&reflect_ray, remaining - 1) * reflective
+    }
+
+    /// The colour seen through a transparent surface, bent according to
+    /// Snell's law. This is black once there are no bounces remaining,
+    /// or under total internal reflection.
+    pub fn refracted_colour(&self, comps: &Computations, remaining: usize) -> Colour {
+        let transparency = self.object(comps.object).material().transparency();
+        if transparency == 0.0 || remaining == 0 {
+            return Colour::black();
+        }
+
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eyev.dot(comps.normalv);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+        if sin2_t > 1.0 {
+            return Colour::black();
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
+        let refract_ray = Ray::new(comps.under_point, direction);
+        self.colour_at(&refract_ray, remaining - 1) * transparency
+    }
+
+    /// Whether anything lies between `point` and `light`.
+    pub fn is_shadowed(&self, light: &PointLight, point: Point3D<f64>) -> bool {
+        let v = light.position() - point;
+        let distance = v.mag();
+        let ray = Ray::new(point, v.normalize());
+
+        match self.intersect(&ray).hit() {
+            Some(hit) => hit.t() < distance,
+            None => false,
+        }
+    }
+
+    pub fn colour_at(&self, ray: &Ray, remaining: usize) -> Colour {
+        let xs = self.intersect(ray);
+        match xs.hit() {
+            Some(hit) => self.shade_hit(&self.prepare_computations(hit, ray, &xs), remaining),
+            None => Colour::black(),
+        }
+    }
+}
+
+impl From<Scene> for World {
+    fn from(scene: Scene) -> Self {
+        World {
+            objects: scene.into_objects(),
+            ..World::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::patterns::{Pattern, Stripe, TestPattern};
+    use crate::shapes::{Csg, CsgOperation, Cylinder};
+
+    /// Computations for a hit which is the only intersection.
+    fn prepare(w: &World, hit: Intersection, r: &Ray) -> Computations {
+        w.prepare_computations(&hit, r, &vec![hit.clone()].into())
+    }
+
+    fn default_world() -> World {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Point3D::new(-10.0, 10.0, -10.0),
+            Colour::white(),
+        ));
+
+        let outer = w.sphere();
+        let m = outer.material_mut();
+        m.set_colour(Colour::new(0.8, 1.0, 0.6));
+        m.set_diffuse(0.7);
+        m.set_specular(0.2);
+
+        let inner = w.sphere();
+        inner.set_transform(Transformation::scaling(0.5, 0.5, 0.5));
+
+        w
+    }
+
+    #[test]
+    fn empty_world() {
+        let w = World::new();
+
+        assert!(w.objects().is_empty());
+        assert!(w.lights().is_empty());
+    }
+
+    #[test]
+    fn from_scene_keeps_ids() {
+        let scene = Scene::new();
+        scene.sphere();
+        scene.sphere();
+        let w = World::from(scene);
+
+        assert_eq!(w.objects().len(), 2);
+        assert_eq!(w.object(1).id(), 1);
+    }
+
+    #[test]
+    fn intersect_world() {
+        let w = default_world();
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = w.intersect(&r);
+        let ts: Vec<f64> = xs.iter().map(|i| i.t()).collect();
+
+        assert_eq!(ts, vec![4.0, 4.5, 5.5, 6.0]);
+    }
+
+    #[test]
+    fn outside_hit() {
+        let w = default_world();
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let comps = prepare(&w, Intersection::new(4.0, 0), &r);
+
+        assert_eq!(comps.t(), 4.0);
+        assert_eq!(comps.object(), 0);
+        assert_eq!(comps.point(), Point3D::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.eyev(), Vector3D::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.normalv(), Vector3D::new(0.0, 0.0, -1.0));
+        assert!(!comps.inside());
+    }
+
+    #[test]
+    fn inside_hit() {
+        let mut w = World::new();
+        w.sphere();
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.0), Vector3D::new(0.0, 0.0, 1.0));
+        let comps = prepare(&w, Intersection::new(1.0, 0), &r);
+
+        assert_eq!(comps.point(), Point3D::new(0.0, 0.0, 1.0));
+        assert_eq!(comps.eyev(), Vector3D::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.normalv(), Vector3D::new(0.0, 0.0, -1.0));
+        assert!(comps.inside());
+    }
+
+    #[test]
+    fn shade_outside() {
+        let w = default_world();
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let comps = prepare(&w, Intersection::new(4.0, 0), &r);
+
+        assert_eq!(
+            w.shade_hit(&comps, DEFAULT_DEPTH),
+            Colour::new(0.38066, 0.47583, 0.2855)
+        );
+    }
+
+    #[test]
+    fn shade_inside() {
+        let mut w = default_world();
+        w.lights.clear();
+        w.add_light(PointLight::new(
+            Point3D::new(0.0, 0.25, 0.0),
+            Colour::white(),
+        ));
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.0), Vector3D::new(0.0, 0.0, 1.0));
+        let comps = prepare(&w, Intersection::new(0.5, 1), &r);
+
+        assert_eq!(
+            w.shade_hit(&comps, DEFAULT_DEPTH),
+            Colour::new(0.90498, 0.90498, 0.90498)
+        );
+    }
+
+    #[test]
+    fn shade_in_shadow() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Point3D::new(0.0, 0.0, -10.0),
+            Colour::white(),
+        ));
+        w.sphere();
+        w.sphere()
+            .set_transform(Transformation::translation(0.0, 0.0, 10.0));
+        let r = Ray::new(Point3D::new(0.0, 0.0, 5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let comps = prepare(&w, Intersection::new(4.0, 1), &r);
+
+        assert_eq!(
+            w.shade_hit(&comps, DEFAULT_DEPTH),
+            Colour::new(0.1, 0.1, 0.1)
+        );
+    }
+
+    #[test]
+    fn hit_offsets_point() {
+        let mut w = World::new();
+        w.sphere()
+            .set_transform(Transformation::translation(0.0, 0.0, 1.0));
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let comps = prepare(&w, Intersection::new(5.0, 0), &r);
+
+        assert!(comps.over_point().z() < -EPSILON / 2.0);
+        assert!(comps.point().z() > comps.over_point().z());
+    }
+
+    #[test]
+    fn nothing_collinear_with_point_and_light() {
+        let w = default_world();
+        let light = &w.lights()[0];
+
+        assert!(!w.is_shadowed(light, Point3D::new(0.0, 10.0, 0.0)));
+    }
+
+    #[test]
+    fn object_between_point_and_light() {
+        let w = default_world();
+        let light = &w.lights()[0];
+
+        assert!(w.is_shadowed(light, Point3D::new(10.0, -10.0, 10.0)));
+    }
+
+    #[test]
+    fn object_behind_light() {
+        let w = default_world();
+        let light = &w.lights()[0];
+
+        assert!(!w.is_shadowed(light, Point3D::new(-20.0, 20.0, -20.0)));
+    }
+
+    #[test]
+    fn object_behind_point() {
+        let w = default_world();
+        let light = &w.lights()[0];
+
+        assert!(!w.is_shadowed(light, Point3D::new(-2.0, 2.0, -2.0)));
+    }
+
+    #[test]
+    fn shadow_cast_on_plane() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Point3D::new(0.0, 10.0, 0.0),
+            Colour::white(),
+        ));
+        w.plane();
+        w.sphere()
+            .set_transform(Transformation::translation(0.0, 2.0, 0.0));
+        let light = &w.lights()[0];
+
+        assert!(w.is_shadowed(light, Point3D::new(0.0, EPSILON, 0.0)));
+        assert!(!w.is_shadowed(light, Point3D::new(5.0, EPSILON, 0.0)));
+    }
+
+    #[test]
+    fn colour_on_miss() {
+        let w = default_world();
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 1.0, 0.0));
+
+        assert_eq!(w.colour_at(&r, DEFAULT_DEPTH), Colour::black());
+    }
+
+    #[test]
+    fn colour_on_hit() {
+        let w = default_world();
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert_eq!(
+            w.colour_at(&r, DEFAULT_DEPTH),
+            Colour::new(0.38066, 0.47583, 0.2855)
+        );
+    }
+
+    #[test]
+    fn colour_behind_ray() {
+        let mut w = default_world();
+        w.objects[0].material_mut().set_ambient(1.0);
+        w.objects[1].material_mut().set_ambient(1.0);
+        let inner_colour = w.object(1).material().colour();
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.75), Vector3D::new(0.0, 0.0, -1.0));
+
+        assert_eq!(w.colour_at(&r, DEFAULT_DEPTH), inner_colour);
+    }
+
+    #[test]
+    fn add_child_to_group() {
+        let mut w = World::new();
+        w.group();
+        w.sphere();
+        w.add_child(0, 1);
+
+        assert_eq!(w.object(0).children(), &[1]);
+        assert_eq!(w.object(1).parent(), Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "only groups can have children")]
+    fn only_groups_have_children() {
+        let mut w = World::new();
+        w.sphere();
+        w.sphere();
+        w.add_child(0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "a group cannot contain itself")]
+    fn group_cycle() {
+        let mut w = World::new();
+        w.group();
+        w.group();
+        w.add_child(0, 1);
+        w.add_child(1, 0);
+    }
+
+    #[test]
+    fn intersect_empty_group() {
+        let mut w = World::new();
+        w.group();
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert!(w.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn intersect_group_children() {
+        let mut w = World::new();
+        w.group();
+        w.sphere();
+        w.sphere()
+            .set_transform(Transformation::translation(0.0, 0.0, -3.0));
+        w.sphere()
+            .set_transform(Transformation::translation(5.0, 0.0, 0.0));
+        for child in 1..4 {
+            w.add_child(0, child);
+        }
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = w.intersect(&r);
+        let objects: Vec<usize> = xs.iter().map(|i| i.object()).collect();
+
+        assert_eq!(objects, vec![2, 2, 1, 1]);
+    }
+
+    #[test]
+    fn intersect_transformed_group() {
+        let mut w = World::new();
+        w.group()
+            .set_transform(Transformation::scaling(2.0, 2.0, 2.0));
+        w.sphere()
+            .set_transform(Transformation::translation(5.0, 0.0, 0.0));
+        w.add_child(0, 1);
+        let r = Ray::new(Point3D::new(10.0, 0.0, -10.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert_eq!(w.intersect(&r).len(), 2);
+    }
+
+    /// A sphere nested two groups deep, each group transformed.
+    fn nested_world(inner_group: Transformation) -> World {
+        use std::f64::consts::PI;
+
+        let mut w = World::new();
+        w.group()
+            .set_transform(Transformation::rotation_y(PI / 2.0));
+        w.group().set_transform(inner_group);
+        w.sphere()
+            .set_transform(Transformation::translation(5.0, 0.0, 0.0));
+        w.add_child(0, 1);
+        w.add_child(1, 2);
+        w
+    }
+
+    #[test]
+    fn point_from_world_to_object() {
+        let w = nested_world(Transformation::scaling(2.0, 2.0, 2.0));
+
+        assert_eq!(
+            w.world_to_object(2, Point3D::new(-2.0, 0.0, -10.0)),
+            Point3D::new(0.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn normal_from_object_to_world() {
+        let w = nested_world(Transformation::scaling(1.0, 2.0, 3.0));
+        let k = 3.0_f64.sqrt() / 3.0;
+
+        assert_eq!(
+            w.normal_to_world(2, Vector3D::new(k, k, k)),
+            Vector3D::new(0.28571, 0.42857, -0.85714)
+        );
+    }
+
+    #[test]
+    fn normal_on_nested_object() {
+        let w = nested_world(Transformation::scaling(1.0, 2.0, 3.0));
+        let hit = Intersection::new(0.0, 2);
+
+        assert_eq!(
+            w.normal_at(2, Point3D::new(1.7321, 1.1547, -5.5774), &hit),
+            Vector3D::new(0.2857, 0.42854, -0.85716)
+        );
+    }
+
+    #[test]
+    fn csg_takes_children() {
+        let mut w = World::new();
+        w.sphere();
+        w.cube();
+        w.add(Csg::new(CsgOperation::Union, 0, 1));
+
+        assert_eq!(w.object(0).parent(), Some(2));
+        assert_eq!(w.object(1).parent(), Some(2));
+    }
+
+    #[test]
+    fn includes_nested_objects() {
+        let mut w = World::new();
+        w.group();
+        w.group();
+        w.sphere();
+        w.add_child(0, 1);
+        w.add_child(1, 2);
+
+        assert!(w.includes(0, 2));
+        assert!(w.includes(1, 1));
+        assert!(!w.includes(2, 0));
+    }
+
+    #[test]
+    fn ray_misses_csg() {
+        let mut w = World::new();
+        w.sphere();
+        w.cube();
+        w.add(Csg::new(CsgOperation::Union, 0, 1));
+        let r = Ray::new(Point3D::new(0.0, 2.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert!(w.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn ray_hits_csg() {
+        let mut w = World::new();
+        w.sphere();
+        w.sphere()
+            .set_transform(Transformation::translation(0.0, 0.0, 0.5));
+        w.add(Csg::new(CsgOperation::Union, 0, 1));
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = w.intersect(&r);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!((xs[0].t(), xs[0].object()), (4.0, 0));
+        assert_eq!((xs[1].t(), xs[1].object()), (6.5, 1));
+    }
+
+    #[test]
+    fn difference_carves_hole() {
+        let mut w = World::new();
+        w.cube();
+        w.sphere()
+            .set_transform(Transformation::scaling(1.5, 1.5, 1.5));
+        w.add(Csg::new(CsgOperation::Difference, 0, 1));
+        // Straight through the middle of a face, where the sphere has
+        // carved the cube away entirely.
+        let through_face = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        // Near an edge, which pokes out beyond the sphere, leaving a
+        // slab of cube either side of it.
+        let through_edge = Ray::new(Point3D::new(0.95, 0.95, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+
+        assert!(w.intersect(&through_face).is_empty());
+        let ts: Vec<f64> = w.intersect(&through_edge).iter().map(|i| i.t()).collect();
+        assert_eq!(ts.len(), 4);
+        assert_eq!((ts[0], ts[3]), (4.0, 6.0));
+    }
+
+    #[test]
+    fn nested_csg_halves() {
+        let mut w = World::new();
+        w.group();
+        w.sphere();
+        w.add_child(0, 1);
+        w.sphere()
+            .set_transform(Transformation::translation(0.0, 0.0, 0.5));
+        w.add(Csg::new(CsgOperation::Intersection, 0, 2));
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = w.intersect(&r);
+        let hits: Vec<(f64, usize)> = xs.iter().map(|i| (i.t(), i.object())).collect();
+
+        assert_eq!(hits, vec![(4.5, 2), (6.0, 1)]);
+    }
+
+    #[test]
+    fn bounds_of_transformed_group() {
+        let mut w = World::new();
+        w.group()
+            .set_transform(Transformation::translation(0.0, 1.0, 0.0));
+        w.sphere()
+            .set_transform(Transformation::translation(2.0, 5.0, -3.0));
+        w.add(Cylinder::truncated(-2.0, 2.0, false))
+            .set_transform(Transformation::translation(-4.0, -1.0, 4.0));
+        w.add_child(0, 1);
+        w.add_child(0, 2);
+        let b = w.bounds(0);
+
+        assert_eq!(b.min(), Point3D::new(-5.0, -2.0, -4.0));
+        assert_eq!(b.max(), Point3D::new(3.0, 7.0, 5.0));
+    }
+
+    #[test]
+    fn hierarchy_matches_exhaustive_search() {
+        let mut w = World::new();
+        w.plane()
+            .set_transform(Transformation::translation(0.0, -3.0, 0.0));
+        let group = w.group();
+        group.set_transform(Transformation::rotation_y(0.3));
+        let group = group.id();
+        for i in 0..60 {
+            let (x, y) = ((i % 10) as f64 - 5.0, (i / 10) as f64 - 3.0);
+            let sphere = w.sphere();
+            sphere.set_transform(
+                Transformation::translation(x, y, 0.0) * Transformation::scaling(0.4, 0.4, 0.4),
+            );
+            let id = sphere.id();
+            w.add_child(group, id);
+        }
+        w.cube();
+        w.sphere()
+            .set_transform(Transformation::scaling(1.3, 1.3, 1.3));
+        w.add(Csg::new(CsgOperation::Difference, 62, 63))
+            .set_transform(Transformation::translation(0.0, 0.0, -4.0));
+
+        for i in 0..200 {
+            let (x, y) = ((i % 20) as f64 * 0.6 - 6.0, (i / 20) as f64 * 0.8 - 4.0);
+            let r = Ray::new(
+                Point3D::new(0.0, 0.0, -10.0),
+                Vector3D::new(x, y, 10.0).normalize(),
+            );
+
+            assert_eq!(w.intersect(&r), w.intersect_exhaustive(&r));
+        }
+    }
+
+    #[test]
+    fn hierarchy_rebuilt_after_changes() {
+        let mut w = World::new();
+        w.sphere();
+        let r = Ray::new(Point3D::new(5.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        assert!(w.intersect(&r).is_empty());
+
+        w.sphere()
+            .set_transform(Transformation::translation(5.0, 0.0, 0.0));
+
+        assert_eq!(w.intersect(&r).len(), 2);
+    }
+
+    #[test]
+    fn hierarchy_rebuilt_after_transform() {
+        let mut w = World::new();
+        let g = w.group().id();
+        let s = w.sphere().id();
+        w.add_child(g, s);
+        let r = Ray::new(Point3D::new(5.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        assert!(w.intersect(&r).is_empty());
+
+        w.set_transform(g, Transformation::translation(5.0, 0.0, 0.0));
+
+        assert_eq!(w.intersect(&r).len(), 2);
+        assert_eq!(w.intersect_exhaustive(&r).len(), 2);
+    }
+
+    #[test]
+    fn precompute_reflection_vector() {
+        let mut w = World::new();
+        w.plane();
+        let k = 2.0_f64.sqrt() / 2.0;
+        let r = Ray::new(Point3D::new(0.0, 1.0, -1.0), Vector3D::new(0.0, -k, k));
+        let comps = prepare(&w, Intersection::new(2.0_f64.sqrt(), 0), &r);
+
+        assert_eq!(comps.reflectv(), Vector3D::new(0.0, k, k));
+    }
+
+    #[test]
+    fn reflect_non_reflective_material() {
+        let mut w = default_world();
+        w.objects[1].material_mut().set_ambient(1.0);
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.0), Vector3D::new(0.0, 0.0, 1.0));
+        let comps = prepare(&w, Intersection::new(1.0, 1), &r);
+
+        assert_eq!(w.reflected_colour(&comps, DEFAULT_DEPTH), Colour::black());
+    }
+
+    /// The default world with a half-reflective plane below it, and a
+    /// ray bouncing off the plane into the outer sphere.
+    fn reflective_plane_world() -> (World, Computations) {
+        let mut w = default_world();
+        let plane = w.plane();
+        plane.material_mut().set_reflective(0.5);
+        plane.set_transform(Transformation::translation(0.0, -1.0, 0.0));
+        let k = 2.0_f64.sqrt() / 2.0;
+        let r = Ray::new(Point3D::new(0.0, 0.0, -3.0), Vector3D::new(0.0, -k, k));
+        let comps = prepare(&w, Intersection::new(2.0_f64.sqrt(), 2), &r);
+
+        (w, comps)
+    }
+
+    #[test]
+    fn reflect_reflective_material() {
+        let (w, comps) = reflective_plane_world();
+
+        assert_eq!(
+            w.reflected_colour(&comps, DEFAULT_DEPTH),
+            Colour::new(0.19033, 0.23792, 0.14275)
+        );
+    }
+
+    #[test]
+    fn shade_reflective_material() {
+        let (w, comps) = reflective_plane_world();
+
+        assert_eq!(
+            w.shade_hit(&comps, DEFAULT_DEPTH),
+            Colour::new(0.87676, 0.92434, 0.82917)
+        );
+    }
+
+    #[test]
+    fn reflect_at_maximum_depth() {
+        let (w, comps) = reflective_plane_world();
+
+        assert_eq!(w.reflected_colour(&comps, 0), Colour::black());
+    }
+
+    #[test]
+    fn mutually_reflective_surfaces() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            Point3D::new(0.0, 0.0, 0.0),
+            Colour::white(),
+        ));
+        let lower = w.plane();
+        lower.material_mut().set_reflective(1.0);
+        lower.set_transform(Transformation::translation(0.0, -1.0, 0.0));
+        let upper = w.plane();
+        upper.material_mut().set_reflective(1.0);
+        upper.set_transform(Transformation::translation(0.0, 1.0, 0.0));
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.0), Vector3D::new(0.0, 1.0, 0.0));
+
+        // All that matters is that this terminates.
+        w.colour_at(&r, DEFAULT_DEPTH);
+    }
+
+    fn glass_sphere(w: &mut World) -> &mut dyn Shape {
+        let sphere = w.sphere();
+        let m = sphere.material_mut();
+        m.set_transparency(1.0);
+        m.set_refractive_index(1.5);
+        sphere
+    }
+
+    fn intersections(hits: &[(f64, usize)]) -> Intersections {
+        hits.iter()
+            .map(|(t, object)| Intersection::new(*t, *object))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    #[test]
+    fn refractive_indices_at_intersections() {
+        let mut w = World::new();
+        let a = glass_sphere(&mut w);
+        a.set_transform(Transformation::scaling(2.0, 2.0, 2.0));
+        let b = glass_sphere(&mut w);
+        b.set_transform(Transformation::translation(0.0, 0.0, -0.25));
+        b.material_mut().set_refractive_index(2.0);
+        let c = glass_sphere(&mut w);
+        c.set_transform(Transformation::translation(0.0, 0.0, 0.25));
+        c.material_mut().set_refractive_index(2.5);
+        let r = Ray::new(Point3D::new(0.0, 0.0, -4.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = intersections(&[
+            (2.0, 0),
+            (2.75, 1),
+            (3.25, 2),
+            (4.75, 1),
+            (5.25, 2),
+            (6.0, 0),
+        ]);
+        let expected = [
+            (1.0, 1.5),
+            (1.5, 2.0),
+            (2.0, 2.5),
+            (2.5, 2.5),
+            (2.5, 1.5),
+            (1.5, 1.0),
+        ];
+
+        for (i, (n1, n2)) in expected.iter().enumerate() {
+            let comps = w.prepare_computations(&xs[i], &r, &xs);
+
+            assert_eq!((comps.n1(), comps.n2()), (*n1, *n2), "intersection {}", i);
+        }
+    }
+
+    #[test]
+    fn hit_offsets_under_point() {
+        let mut w = World::new();
+        glass_sphere(&mut w).set_transform(Transformation::translation(0.0, 0.0, 1.0));
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let comps = prepare(&w, Intersection::new(5.0, 0), &r);
+
+        assert!(comps.under_point().z() > EPSILON / 2.0);
+        assert!(comps.point().z() < comps.under_point().z());
+    }
+
+    #[test]
+    fn refract_opaque_surface() {
+        let w = default_world();
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = intersections(&[(4.0, 0), (6.0, 0)]);
+        let comps = w.prepare_computations(&xs[0], &r, &xs);
+
+        assert_eq!(w.refracted_colour(&comps, DEFAULT_DEPTH), Colour::black());
+    }
+
+    #[test]
+    fn refract_at_maximum_depth() {
+        let mut w = default_world();
+        let m = w.objects[0].material_mut();
+        m.set_transparency(1.0);
+        m.set_refractive_index(1.5);
+        let r = Ray::new(Point3D::new(0.0, 0.0, -5.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = intersections(&[(4.0, 0), (6.0, 0)]);
+        let comps = w.prepare_computations(&xs[0], &r, &xs);
+
+        assert_eq!(w.refracted_colour(&comps, 0), Colour::black());
+    }
+
+    #[test]
+    fn total_internal_reflection() {
+        let mut w = default_world();
+        let m = w.objects[0].material_mut();
+        m.set_transparency(1.0);
+        m.set_refractive_index(1.5);
+        let k = 2.0_f64.sqrt() / 2.0;
+        let r = Ray::new(Point3D::new(0.0, 0.0, k), Vector3D::new(0.0, 1.0, 0.0));
+        let xs = intersections(&[(-k, 0), (k, 0)]);
+        // Inside the sphere, so the second intersection is the one that
+        // matters.
+        let comps = w.prepare_computations(&xs[1], &r, &xs);
+
+        assert_eq!(w.refracted_colour(&comps, DEFAULT_DEPTH), Colour::black());
+    }
+
+    /// The default world with a floor below it, over a red ball, with a
+    /// ray striking the floor at 45 degrees.
+    fn floor_over_ball(reflective: f64) -> (World, Computations) {
+        let mut w = default_world();
+        let floor = w.plane();
+        floor.set_transform(Transformation::translation(0.0, -1.0, 0.0));
+        let m = floor.material_mut();
+        m.set_reflective(reflective);
+        m.set_transparency(0.5);
+        m.set_refractive_index(1.5);
+        let ball = w.sphere();
+        ball.set_transform(Transformation::translation(0.0, -3.5, -0.5));
+        let m = ball.material_mut();
+        m.set_colour(Colour::new(1.0, 0.0, 0.0));
+        m.set_ambient(0.5);
+        let k = 2.0_f64.sqrt() / 2.0;
+        let r = Ray::new(Point3D::new(0.0, 0.0, -3.0), Vector3D::new(0.0, -k, k));
+        let comps = prepare(&w, Intersection::new(2.0_f64.sqrt(), 2), &r);
+
+        (w, comps)
+    }
+
+    #[test]
+    fn shade_transparent_material() {
+        let (w, comps) = floor_over_ball(0.0);
+
+        assert_eq!(
+            w.shade_hit(&comps, DEFAULT_DEPTH),
+            Colour::new(0.93643, 0.68643, 0.68643)
+        );
+    }
+
+    #[test]
+    fn shade_reflective_transparent_material() {
+        let (w, comps) = floor_over_ball(0.5);
+
+        assert_eq!(
+            w.shade_hit(&comps, DEFAULT_DEPTH),
+            Colour::new(0.93391, 0.69643, 0.69243)
+        );
+    }
+
+    #[test]
+    fn schlick_under_total_internal_reflection() {
+        let mut w = World::new();
+        glass_sphere(&mut w);
+        let k = 2.0_f64.sqrt() / 2.0;
+        let r = Ray::new(Point3D::new(0.0, 0.0, k), Vector3D::new(0.0, 1.0, 0.0));
+        let xs = intersections(&[(-k, 0), (k, 0)]);
+        let comps = w.prepare_computations(&xs[1], &r, &xs);
+
+        assert_eq!(comps.schlick(), 1.0);
+    }
+
+    #[test]
+    fn schlick_perpendicular_ray() {
+        let mut w = World::new();
+        glass_sphere(&mut w);
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.0), Vector3D::new(0.0, 1.0, 0.0));
+        let xs = intersections(&[(-1.0, 0), (1.0, 0)]);
+        let comps = w.prepare_computations(&xs[1], &r, &xs);
+
+        assert!((comps.schlick() - 0.04).abs() < EPSILON);
+    }
+
+    #[test]
+    fn schlick_small_angle() {
+        let mut w = World::new();
+        glass_sphere(&mut w);
+        let r = Ray::new(Point3D::new(0.0, 0.99, -2.0), Vector3D::new(0.0, 0.0, 1.0));
+        let xs = intersections(&[(1.8589, 0)]);
+        let comps = w.prepare_computations(&xs[0], &r, &xs);
+
+        assert!((comps.schlick() - 0.48873).abs() < EPSILON);
+    }
+
+    #[test]
+    fn pattern_follows_object_transformation() {
+        let mut w = World::new();
+        let sphere = w.sphere();
+        sphere.set_transform(Transformation::scaling(2.0, 2.0, 2.0));
+        sphere
+            .material_mut()
+            .set_pattern(Stripe::new(Colour::white(), Colour::black()));
+        let point = w.world_to_object(0, Point3D::new(1.5, 0.0, 0.0));
+
+        assert_eq!(w.object(0).material().colour_at(point), Colour::white());
+    }
+
+    #[test]
+    fn pattern_and_object_transformations() {
+        let mut w = World::new();
+        w.group()
+            .set_transform(Transformation::translation(0.5, 0.0, 0.0));
+        let sphere = w.sphere();
+        sphere.set_transform(Transformation::scaling(2.0, 2.0, 2.0));
+        let mut pattern = TestPattern::default();
+        pattern.set_transform(Transformation::translation(0.5, 1.0, 1.5));
+        sphere.material_mut().set_pattern(pattern);
+        w.add_child(0, 1);
+        let point = w.world_to_object(1, Point3D::new(3.0, 3.0, 3.0));
+
+        assert_eq!(
+            w.object(1).material().colour_at(point),
+            Colour::new(0.75, 0.5, 0.0)
+        );
+    }
+
+    #[test]
+    fn refract_through_surface() {
+        let mut w = default_world();
+        let a = w.objects[0].material_mut();
+        a.set_ambient(1.0);
+        a.set_pattern(TestPattern::default());
+        let b = w.objects[1].material_mut();
+        b.set_transparency(1.0);
+        b.set_refractive_index(1.5);
+        let r = Ray::new(Point3D::new(0.0, 0.0, 0.1), Vector3D::new(0.0, 1.0, 0.0));
+        let xs = intersections(&[(-0.9899, 0), (-0.4899, 1), (0.4899, 1), (0.9899, 0)]);
+        let comps = w.prepare_computations(&xs[2], &r, &xs);
+
+        // Blue is sensitive to how far below the surface the refracted
+        // ray starts, and so to EPSILON.
+        assert_eq!(
+            w.refracted_colour(&comps, DEFAULT_DEPTH),
+            Colour::new(0.0, 0.99887, 0.04722)
+        );
+    }
+}